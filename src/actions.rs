@@ -0,0 +1,322 @@
+//! Actions simulate low level inputs: keyboard, mouse, pen, touch and wheel
+
+use json::*;
+use std::time::Duration;
+use crate::elements::ElementRef;
+use crate::keys::{Keys, is_modifier};
+
+/// The kind of device a [PointerSource](struct.PointerSource.html) simulates.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub enum PointerType {
+    Mouse,
+    Pen,
+    Touch
+}
+
+impl PointerType {
+    pub fn to_string(self) -> &'static str {
+        match self {
+            PointerType::Mouse => "mouse",
+            PointerType::Pen => "pen",
+            PointerType::Touch => "touch"
+        }
+    }
+}
+
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    Back,
+    Forward
+}
+
+impl MouseButton {
+    pub fn to_number(self) -> u8 {
+        match self {
+            MouseButton::Left => 0,
+            MouseButton::Middle => 1,
+            MouseButton::Right => 2,
+            MouseButton::Back => 3,
+            MouseButton::Forward => 4
+        }
+    }
+}
+
+/// What the coordinates of a move or a scroll are relative to.
+#[derive(Debug)]
+#[derive(Clone)]
+pub enum Origin {
+    /// The top left corner of the viewport.
+    Viewport,
+    /// The current position of the pointer (not allowed for scrolls).
+    Pointer,
+    /// The center of an element, like `Origin::Element(element.into())`.
+    Element(ElementRef)
+}
+
+impl Origin {
+    pub fn to_json(&self) -> JsonValue {
+        match self {
+            Origin::Viewport => "viewport".into(),
+            Origin::Pointer => "pointer".into(),
+            Origin::Element(element) => element.as_json_object()
+        }
+    }
+}
+
+fn pause(duration: Option<Duration>) -> JsonValue {
+    let mut action = object!{ "type" => "pause" };
+    if let Some(duration) = duration {
+        action["duration"] = (duration.as_millis() as u64).into();
+    }
+    action
+}
+
+/// A keyboard.
+/// Each action takes one tick.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct KeySource {
+    id: String,
+    actions: Vec<JsonValue>
+}
+
+impl KeySource {
+    /// The id identifies the device across [perform_actions()](../tab/struct.Tab.html#method.perform_actions) calls, so keys stay pressed.
+    pub fn new(id: &str) -> Self {
+        KeySource {
+            id: id.to_string(),
+            actions: Vec::new()
+        }
+    }
+
+    /// Do nothing during a tick, or wait for a duration.
+    pub fn pause(mut self, duration: Option<Duration>) -> Self {
+        self.actions.push(pause(duration));
+        self
+    }
+
+    /// Press a key. It can be a character or one of the special [Keys](../keys/enum.Keys.html).
+    pub fn key_down<K: Into<char>>(mut self, key: K) -> Self {
+        self.actions.push(object!{ "type" => "keyDown", "value" => key.into().to_string() });
+        self
+    }
+
+    pub fn key_up<K: Into<char>>(mut self, key: K) -> Self {
+        self.actions.push(object!{ "type" => "keyUp", "value" => key.into().to_string() });
+        self
+    }
+
+    /// Type a text which can contain special keys (see [KeySequence](../keys/struct.KeySequence.html)).
+    /// Modifiers stay pressed until the [Null](../keys/enum.Keys.html#variant.Null) key or the end of the text.
+    pub fn type_text(mut self, text: &str) -> Self {
+        let mut modifiers = Vec::new();
+        for key in text.chars() {
+            if key == Keys::Null.to_char() {
+                for modifier in modifiers.drain(..).rev() {
+                    self = self.key_up(modifier);
+                }
+            } else if is_modifier(key) {
+                if !modifiers.contains(&key) {
+                    modifiers.push(key);
+                    self = self.key_down(key);
+                }
+            } else {
+                self = self.key_down(key).key_up(key);
+            }
+        }
+        for modifier in modifiers.into_iter().rev() {
+            self = self.key_up(modifier);
+        }
+        self
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        object!{
+            "type" => "key",
+            "id" => self.id.as_str(),
+            "actions" => self.actions.clone()
+        }
+    }
+}
+
+/// A mouse, a pen or a finger.
+/// Each action takes one tick.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct PointerSource {
+    id: String,
+    pointer_type: PointerType,
+    actions: Vec<JsonValue>
+}
+
+impl PointerSource {
+    /// The id identifies the device across [perform_actions()](../tab/struct.Tab.html#method.perform_actions) calls, so buttons stay pressed.
+    pub fn new(id: &str, pointer_type: PointerType) -> Self {
+        PointerSource {
+            id: id.to_string(),
+            pointer_type,
+            actions: Vec::new()
+        }
+    }
+
+    /// Do nothing during a tick, or wait for a duration.
+    pub fn pause(mut self, duration: Option<Duration>) -> Self {
+        self.actions.push(pause(duration));
+        self
+    }
+
+    /// Press a button (or touch the screen).
+    pub fn down(mut self, button: MouseButton) -> Self {
+        self.actions.push(object!{ "type" => "pointerDown", "button" => button.to_number() });
+        self
+    }
+
+    pub fn up(mut self, button: MouseButton) -> Self {
+        self.actions.push(object!{ "type" => "pointerUp", "button" => button.to_number() });
+        self
+    }
+
+    /// Move the pointer to a position relative to an [origin](enum.Origin.html), during a duration.
+    pub fn move_to(mut self, x: i64, y: i64, origin: Origin, duration: Duration) -> Self {
+        self.actions.push(object!{
+            "type" => "pointerMove",
+            "x" => x,
+            "y" => y,
+            "origin" => origin.to_json(),
+            "duration" => duration.as_millis() as u64
+        });
+        self
+    }
+
+    /// Move the pointer to the center of an element instantly.
+    pub fn move_to_element<E: Into<ElementRef>>(self, element: E) -> Self {
+        self.move_to(0, 0, Origin::Element(element.into()), Duration::from_millis(0))
+    }
+
+    pub fn cancel(mut self) -> Self {
+        self.actions.push(object!{ "type" => "pointerCancel" });
+        self
+    }
+
+    /// Press and release a button.
+    pub fn click(self, button: MouseButton) -> Self {
+        self.down(button).up(button)
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        object!{
+            "type" => "pointer",
+            "id" => self.id.as_str(),
+            "parameters" => object!{ "pointerType" => self.pointer_type.to_string() },
+            "actions" => self.actions.clone()
+        }
+    }
+}
+
+/// A scroll wheel.
+/// Each action takes one tick.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct WheelSource {
+    id: String,
+    actions: Vec<JsonValue>
+}
+
+impl WheelSource {
+    pub fn new(id: &str) -> Self {
+        WheelSource {
+            id: id.to_string(),
+            actions: Vec::new()
+        }
+    }
+
+    /// Do nothing during a tick, or wait for a duration.
+    pub fn pause(mut self, duration: Option<Duration>) -> Self {
+        self.actions.push(pause(duration));
+        self
+    }
+
+    /// Scroll by `delta_x` and `delta_y` pixels at a position relative to an [origin](enum.Origin.html) (the viewport or an element).
+    pub fn scroll(mut self, x: i64, y: i64, delta_x: i64, delta_y: i64, origin: Origin, duration: Duration) -> Self {
+        self.actions.push(object!{
+            "type" => "scroll",
+            "x" => x,
+            "y" => y,
+            "deltaX" => delta_x,
+            "deltaY" => delta_y,
+            "origin" => origin.to_json(),
+            "duration" => duration.as_millis() as u64
+        });
+        self
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        object!{
+            "type" => "wheel",
+            "id" => self.id.as_str(),
+            "actions" => self.actions.clone()
+        }
+    }
+}
+
+/// A sequence of inputs performed by [Tab::perform_actions()](../tab/struct.Tab.html#method.perform_actions).
+///
+/// Every source performs its actions in parallel: at each tick, the n-th action of every source is dispatched.
+/// Use pauses to wait for other sources.
+///
+/// # Example
+///
+/// ```rust
+/// # use lw_webdriver::{session::Session, enums::{Browser, Selector}};
+/// use lw_webdriver::{actions::*, keys::Keys};
+///
+/// let mut session = Session::new(Browser::Firefox, false).unwrap();
+/// session.tabs[0].navigate("http://example.com/").unwrap();
+/// let link = session.tabs[0].find(Selector::Css, "a").unwrap().unwrap();
+///
+/// // shift + click on a link
+/// let actions = Actions::new()
+///     .key(KeySource::new("keyboard").key_down(Keys::Shift).pause(None).pause(None).key_up(Keys::Shift))
+///     .pointer(PointerSource::new("mouse", PointerType::Mouse).pause(None).move_to_element(&link).click(MouseButton::Left));
+/// session.tabs[0].perform_actions(&actions).unwrap();
+/// ```
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct Actions {
+    sources: Vec<JsonValue>
+}
+
+impl Actions {
+    pub fn new() -> Self {
+        Actions::default()
+    }
+
+    pub fn key(mut self, source: KeySource) -> Self {
+        self.sources.push(source.to_json());
+        self
+    }
+
+    pub fn pointer(mut self, source: PointerSource) -> Self {
+        self.sources.push(source.to_json());
+        self
+    }
+
+    pub fn wheel(mut self, source: WheelSource) -> Self {
+        self.sources.push(source.to_json());
+        self
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        object!{
+            "actions" => self.sources.clone()
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Alerts are the dialogs opened by `alert()`, `confirm()` and `prompt()`
+
+use std::result::Result;
+use crate::tab::Tab;
+use crate::error::WebdriverError;
+use crate::http_requests::{get_alert_text, close_alert, send_alert_text};
+
+/// A dialog opened in a tab, returned by [Tab::alert()](../tab/struct.Tab.html#method.alert).
+/// Closing the dialog consumes the handle.
+///
+/// # Example
+///
+/// ```rust
+/// # use lw_webdriver::{session::Session, enums::Browser};
+/// let mut session = Session::new(Browser::Firefox, false).unwrap();
+/// session.tabs[0].execute_script("setTimeout(() => window.answer = confirm('Leave the page?'));", vec![]).unwrap();
+/// # std::thread::sleep(std::time::Duration::from_millis(100));
+///
+/// if let Some(alert) = session.tabs[0].alert().unwrap() {
+///     assert_eq!(alert.text().unwrap(), "Leave the page?");
+///     alert.accept().unwrap();
+/// }
+/// ```
+pub struct Alert<'a> {
+    tab: &'a Tab
+}
+
+impl<'a> Alert<'a> {
+    pub(crate) fn new(tab: &'a Tab) -> Self {
+        Alert {
+            tab
+        }
+    }
+
+    /// The message of the dialog.
+    pub fn text(&self) -> Result<String, WebdriverError> {
+        self.tab.run(get_alert_text(&self.tab.session_id))
+    }
+
+    /// Type a text in a `prompt()` dialog.
+    pub fn send_keys(&self, text: &str) -> Result<(), WebdriverError> {
+        self.tab.run(send_alert_text(&self.tab.session_id, text))
+    }
+
+    /// Click on "OK".
+    pub fn accept(self) -> Result<(), WebdriverError> {
+        self.tab.run(close_alert(&self.tab.session_id, "accept"))
+    }
+
+    /// Click on "Cancel" (or close an `alert()` dialog).
+    pub fn dismiss(self) -> Result<(), WebdriverError> {
+        self.tab.run(close_alert(&self.tab.session_id, "dismiss"))
+    }
+}
@@ -0,0 +1,620 @@
+//! Async twins of [Session](../session/struct.Session.html), [Tab](../tab/struct.Tab.html) and [Element](../elements/struct.Element.html) (requires the `async` feature)
+//!
+//! They send the same commands as the blocking API, with [reqwest](https://crates.io/crates/reqwest) by default.
+//! Handles are `Send` and tabs and elements can be cloned, so they can be moved into tasks.
+//! Commands of different tabs are serialized internally, so that a command is never sent to the wrong tab.
+//!
+//! Some helpers have no async versions yet: the [Alert](../alerts/struct.Alert.html) and [Frame](../frames/struct.Frame.html) guards,
+//! [Wait](../wait/struct.Wait.html), and the element actions ([hover()](../elements/struct.Element.html#method.hover), [drag_and_drop_to()](../elements/struct.Element.html#method.drag_and_drop_to)...).
+//! [AsyncTab](struct.AsyncTab.html) has plain methods to handle dialogs, switch frames and perform [Actions](../actions/struct.Actions.html) instead.
+//!
+//! # Example
+//!
+//! ```rust
+//! use lw_webdriver::{session::SessionBuilder, enums::{Browser, Selector}};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let mut session = SessionBuilder::new(Browser::Firefox).headless(true).build_async().await.unwrap();
+//!
+//! session.tabs[0].navigate("http://example.com/").await.unwrap();
+//! let link = session.tabs[0].find(Selector::Css, "a").await.unwrap().unwrap();
+//! assert_eq!(link.get_text().await.unwrap(), "More information...");
+//!
+//! session.quit().await.unwrap();
+//! # }
+//! ```
+
+use json::*;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::result::Result;
+use std::sync::Arc;
+use log::{info, warn, error};
+use tokio::sync::Mutex;
+use crate::enums::{Selector, WebdriverObject};
+use crate::error::{WebdriverError, ErrorKind};
+use crate::timeouts::Timeouts;
+use crate::cookies::Cookie;
+use crate::screenshot::Screenshot;
+use crate::print::PrintOptions;
+use crate::actions::Actions;
+use crate::frames::FrameId;
+use crate::elements::ElementRef;
+use crate::window::WindowRect;
+use crate::script::ScriptValue;
+use crate::service::{DriverService, DriverOutput};
+use crate::transport::{Request, Response, Method};
+use crate::http_requests::*;
+
+/// The future returned by an [AsyncTransport](trait.AsyncTransport.html).
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<Response, Box<dyn Error + Send + Sync>>> + Send + 'a>>;
+
+/// The async equivalent of a [Transport](../transport/trait.Transport.html).
+/// The default one is [ReqwestTransport](struct.ReqwestTransport.html).
+pub trait AsyncTransport: Send + Sync {
+    fn send<'a>(&'a self, request: &'a Request) -> TransportFuture<'a>;
+}
+
+/// Send requests with [reqwest](https://crates.io/crates/reqwest).
+/// Requests must be sent from a tokio runtime.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client
+}
+
+impl ReqwestTransport {
+    /// Use an already configured reqwest client (for example with a proxy).
+    pub fn new(client: reqwest::Client) -> Self {
+        ReqwestTransport {
+            client
+        }
+    }
+}
+
+impl AsyncTransport for ReqwestTransport {
+    fn send<'a>(&'a self, request: &'a Request) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let mut reqwest_request = match request.method {
+                Method::Get => self.client.get(request.url.as_str()),
+                Method::Post => self.client.post(request.url.as_str()),
+                Method::Delete => self.client.delete(request.url.as_str())
+            };
+            if let Some(body) = &request.body {
+                reqwest_request = reqwest_request.header("Content-Type", "application/json").body(body.clone());
+            }
+            let response = reqwest_request.send().await?;
+            let status = response.status().as_u16();
+
+            Ok(Response {
+                status,
+                body: response.bytes().await?.to_vec()
+            })
+        })
+    }
+}
+
+/// Everything needed to send commands to a webdriver, shared by a session and all its tabs and elements.
+pub(crate) struct AsyncClient {
+    pub(crate) url: String,
+    pub(crate) transport: Box<dyn AsyncTransport>,
+    pub(crate) driver_output: Option<DriverOutput>,
+    /// Held while a tab is selected and used
+    selection: Mutex<()>
+}
+
+impl AsyncClient {
+    pub(crate) fn new(url: &str) -> Self {
+        AsyncClient {
+            url: url.trim_end_matches('/').to_string(),
+            transport: Box::new(ReqwestTransport::default()),
+            driver_output: None,
+            selection: Mutex::new(())
+        }
+    }
+
+    /// Send a command to the webdriver and read the response
+    async fn run<T>(&self, command: Command<T>) -> Result<T, WebdriverError> {
+        let request = command.request(&self.url);
+        let response = self.transport.send(&request).await;
+        command.read_response(response, self.driver_output.as_ref())
+    }
+
+    /// Select a tab and send a command to it.
+    /// Other tabs wait, so that they can't select themselves in between.
+    async fn run_in_tab<T>(&self, session_id: &str, tab_id: &str, command: Command<T>) -> Result<T, WebdriverError> {
+        let _selection = self.selection.lock().await;
+
+        // check if it is needed to select the tab
+        match self.run(get_selected_tab(session_id)).await {
+            Ok(id) if id == tab_id => (),
+            _ => self.run(select_tab(session_id, tab_id)).await?
+        }
+
+        self.run(command).await
+    }
+}
+
+/// The async equivalent of a [Session](../session/struct.Session.html), created by [SessionBuilder::build_async()](../session/struct.SessionBuilder.html#method.build_async).
+///
+/// Futures can't run when a value is dropped, so call [quit()](#method.quit) to end the session.
+/// A session dropped without calling it leaves the browser open (unless the webdriver was launched with a [DriverService](../service/struct.DriverService.html), which is killed).
+pub struct AsyncSession {
+    id: Arc<String>,
+    client: Arc<AsyncClient>,
+    /// Contains every manually created tabs and default tab.
+    pub tabs: Vec<AsyncTab>,
+    webdriver_process: Option<DriverService>,
+    keep_open: bool,
+    ended: bool
+}
+
+impl AsyncSession {
+    /// Create a session of a specific [browser](../enums/enum.Browser.html) on a webdriver server listening at `webdriver_url`.
+    /// No webdriver is launched if none is listening.
+    pub async fn new_with_url(webdriver_url: &str, browser: crate::enums::Browser, headless: bool) -> Result<Self, WebdriverError> {
+        crate::session::SessionBuilder::new(browser)
+            .webdriver_url(webdriver_url)
+            .headless(headless)
+            .build_async()
+            .await
+    }
+
+    pub(crate) async fn new_from_capabilities(client: AsyncClient, capabilities: &JsonValue, webdriver_process: Option<DriverService>, keep_open: bool) -> Result<Self, WebdriverError> {
+        let session_id = client.run(new_session(&capabilities.to_string())).await?;
+        let mut session = AsyncSession {
+            id: Arc::new(session_id),
+            client: Arc::new(client),
+            tabs: Vec::new(),
+            webdriver_process,
+            keep_open,
+            ended: false
+        };
+
+        session.update_tabs().await?;
+
+        Ok(session)
+    }
+
+    /// Create a new tab in the session and return its index in [tabs](#structfield.tabs).
+    pub async fn open_tab(&mut self) -> Result<usize, WebdriverError> {
+        let tab_id = self.client.run(new_tab(&self.id)).await?;
+        self.tabs.push(AsyncTab::new_from(tab_id, Arc::clone(&self.id), Arc::clone(&self.client)));
+
+        Ok(self.tabs.len() - 1)
+    }
+
+    /// Add the tabs opened by web pages to [tabs](#structfield.tabs).
+    /// See [Session::update_tabs()](../session/struct.Session.html#method.update_tabs).
+    pub async fn update_tabs(&mut self) -> Result<(), WebdriverError> {
+        let tabs_id = self.client.run(get_open_tabs(&self.id)).await?;
+        for tab_id in tabs_id {
+            if self.tabs.iter().all(|tab| *tab.id != tab_id) {
+                self.tabs.push(AsyncTab::new_from(tab_id, Arc::clone(&self.id), Arc::clone(&self.client)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return the url of the webdriver server this session is running on.
+    pub fn get_webdriver_url(&self) -> &str {
+        &self.client.url
+    }
+
+    /// Leave the browser (and the webdriver launched by this crate, if any) running when the session is dropped.
+    pub fn set_keep_open(&mut self, keep_open: bool) {
+        self.keep_open = keep_open;
+    }
+
+    /// End the session and close the browser.
+    pub async fn quit(mut self) -> Result<(), WebdriverError> {
+        self.tabs.clear();
+        self.ended = true;
+        self.client.run(delete_session(&self.id)).await
+    }
+
+    pub async fn get_timeouts(&self) -> Result<Timeouts, WebdriverError> {
+        self.client.run(get_timeouts(&self.id)).await
+    }
+
+    pub async fn set_timeouts(&mut self, timeouts: Timeouts) -> Result<(), WebdriverError> {
+        self.client.run(set_timeouts(&self.id, timeouts)).await
+    }
+}
+
+impl PartialEq for AsyncSession {
+    fn eq(&self, other: &Self) -> bool {
+        self.get_id() == other.get_id()
+    }
+}
+
+impl WebdriverObject for AsyncSession {
+    fn get_id(&self) -> &String {
+        &self.id
+    }
+}
+
+impl Drop for AsyncSession {
+    fn drop(&mut self) {
+        if self.keep_open {
+            if let Some(webdriver_process) = self.webdriver_process.take() {
+                info!("Leaving the webdriver process running");
+                std::mem::forget(webdriver_process);
+            }
+            return;
+        }
+        if !self.ended {
+            warn!("The async session {} was dropped without calling quit()", self.id);
+        }
+    }
+}
+
+/// The async equivalent of a [Tab](../tab/struct.Tab.html).
+/// Clones control the same tab.
+/// Tabs are not closed when dropped: use [close()](#method.close).
+#[derive(Clone)]
+pub struct AsyncTab {
+    id: Arc<String>,
+    session_id: Arc<String>,
+    client: Arc<AsyncClient>
+}
+
+impl AsyncTab {
+    fn new_from(id: String, session_id: Arc<String>, client: Arc<AsyncClient>) -> AsyncTab {
+        AsyncTab {
+            id: Arc::new(id),
+            session_id,
+            client
+        }
+    }
+
+    async fn run<T>(&self, command: Command<T>) -> Result<T, WebdriverError> {
+        self.client.run_in_tab(&self.session_id, &self.id, command).await
+    }
+
+    fn element(&self, id: String) -> AsyncElement {
+        AsyncElement {
+            id,
+            session_id: Arc::clone(&self.session_id),
+            tab_id: Arc::clone(&self.id),
+            client: Arc::clone(&self.client)
+        }
+    }
+
+    /// Load a website
+    pub async fn navigate(&self, url: &str) -> Result<(), WebdriverError> {
+        self.run(navigate(&self.session_id, url)).await
+    }
+
+    /// Run the next commands of this tab inside a frame of the current document (or the top-level document).
+    pub async fn switch_to_frame(&self, frame: FrameId) -> Result<(), WebdriverError> {
+        self.run(switch_to_frame(&self.session_id, &frame)).await
+    }
+
+    /// Leave the current frame for its parent.
+    pub async fn switch_to_parent_frame(&self) -> Result<(), WebdriverError> {
+        self.run(switch_to_parent_frame(&self.session_id)).await
+    }
+
+    /// Find an element in the tab, selected by a [Selector](../enums/enum.Selector.html).
+    pub async fn find(&self, selector: Selector, tofind: &str) -> Result<Option<AsyncElement>, WebdriverError> {
+        match self.run(find_element(&self.session_id, selector, tofind)).await {
+            Ok(id) => Ok(Some(self.element(id))),
+            Err(error) if error == ErrorKind::NoSuchElement => Ok(None),
+            Err(error) => Err(error)
+        }
+    }
+
+    /// Find every element in the tab matching a [Selector](../enums/enum.Selector.html).
+    pub async fn find_all(&self, selector: Selector, tofind: &str) -> Result<Vec<AsyncElement>, WebdriverError> {
+        let ids = self.run(find_elements(&self.session_id, selector, tofind)).await?;
+        Ok(ids.into_iter().map(|id| self.element(id)).collect())
+    }
+
+    /// Return the url of the current web page.
+    pub async fn get_url(&self) -> Result<String, WebdriverError> {
+        self.run(get_active_tab_url(&self.session_id)).await
+    }
+
+    /// Return the title of the tab.
+    pub async fn get_title(&self) -> Result<String, WebdriverError> {
+        self.run(get_active_tab_title(&self.session_id)).await
+    }
+
+    /// Navigate to the previous page.
+    pub async fn back(&self) -> Result<(), WebdriverError> {
+        self.run(back(&self.session_id)).await
+    }
+
+    /// Navigate forward.
+    pub async fn forward(&self) -> Result<(), WebdriverError> {
+        self.run(forward(&self.session_id)).await
+    }
+
+    /// Refresh the page.
+    pub async fn refresh(&self) -> Result<(), WebdriverError> {
+        self.run(refresh(&self.session_id)).await
+    }
+
+    /// Execute a script in the tab and return the value it returns.
+    /// Elements are returned as [AsyncElements](struct.AsyncElement.html).
+    /// See [Tab::execute_script()](../tab/struct.Tab.html#method.execute_script).
+    pub async fn execute_script(&self, script: &str, args: Vec<JsonValue>) -> Result<ScriptValue<AsyncElement>, WebdriverError> {
+        let value = self.run(execute_script_sync(&self.session_id, script, args)).await?;
+        Ok(ScriptValue::from_json_with(&value, &|id| self.element(id)))
+    }
+
+    /// Execute an asynchronous script in the tab and return the value it completes with.
+    /// See [Tab::execute_async_script()](../tab/struct.Tab.html#method.execute_async_script).
+    pub async fn execute_async_script(&self, script: &str, args: Vec<JsonValue>) -> Result<ScriptValue<AsyncElement>, WebdriverError> {
+        let value = self.run(execute_script_async(&self.session_id, script, args)).await?;
+        Ok(ScriptValue::from_json_with(&value, &|id| self.element(id)))
+    }
+
+    /// Return every cookie visible by the current page, including session cookies.
+    pub async fn get_cookies(&self) -> Result<Vec<Cookie>, WebdriverError> {
+        self.run(get_all_cookies(&self.session_id)).await
+    }
+
+    /// Return the cookie with this name, or `None` if there is no such cookie.
+    pub async fn get_cookie(&self, name: &str) -> Result<Option<Cookie>, WebdriverError> {
+        match self.run(get_named_cookie(&self.session_id, name)).await {
+            Ok(cookie) => Ok(Some(cookie)),
+            Err(error) if error == ErrorKind::NoSuchCookie => Ok(None),
+            Err(error) => Err(error)
+        }
+    }
+
+    /// Add a cookie to the current page.
+    pub async fn set_cookie(&self, cookie: &Cookie) -> Result<(), WebdriverError> {
+        self.run(set_cookie(&self.session_id, cookie)).await
+    }
+
+    /// Delete the cookie with this name.
+    pub async fn delete_cookie(&self, name: &str) -> Result<(), WebdriverError> {
+        self.run(delete_cookie(&self.session_id, name)).await
+    }
+
+    /// Delete every cookie visible by the current page.
+    pub async fn delete_all_cookies(&self) -> Result<(), WebdriverError> {
+        self.run(delete_all_cookies(&self.session_id)).await
+    }
+
+    pub async fn get_page_source(&self) -> Result<String, WebdriverError> {
+        self.run(get_page_source(&self.session_id)).await
+    }
+
+    /// Perform low level inputs (see [Actions](../actions/struct.Actions.html)).
+    pub async fn perform_actions(&self, actions: &Actions) -> Result<(), WebdriverError> {
+        self.run(perform_actions(&self.session_id, actions)).await
+    }
+
+    /// Release every key and button pressed by [perform_actions()](#method.perform_actions).
+    pub async fn release_actions(&self) -> Result<(), WebdriverError> {
+        self.run(release_actions(&self.session_id)).await
+    }
+
+    /// Return the message of the dialog opened in this tab, or `None` if there is no dialog.
+    pub async fn get_alert_text(&self) -> Result<Option<String>, WebdriverError> {
+        match self.run(get_alert_text(&self.session_id)).await {
+            Ok(text) => Ok(Some(text)),
+            Err(error) if error == ErrorKind::NoSuchAlert => Ok(None),
+            Err(error) => Err(error)
+        }
+    }
+
+    /// Type a text in the opened `prompt()`.
+    pub async fn send_alert_text(&self, text: &str) -> Result<(), WebdriverError> {
+        self.run(send_alert_text(&self.session_id, text)).await
+    }
+
+    /// Click "OK" on the opened dialog.
+    pub async fn accept_alert(&self) -> Result<(), WebdriverError> {
+        self.run(close_alert(&self.session_id, "accept")).await
+    }
+
+    /// Click "Cancel" on the opened dialog.
+    pub async fn dismiss_alert(&self) -> Result<(), WebdriverError> {
+        self.run(close_alert(&self.session_id, "dismiss")).await
+    }
+
+    /// Return the position and the size of the window containing this tab.
+    pub async fn get_window_rect(&self) -> Result<WindowRect, WebdriverError> {
+        self.run(get_window_rect(&self.session_id)).await
+    }
+
+    /// Move and resize the window containing this tab, and return the resulting rect.
+    pub async fn set_window_rect(&self, rect: &WindowRect) -> Result<WindowRect, WebdriverError> {
+        self.run(set_window_rect(&self.session_id, &rect.to_json())).await
+    }
+
+    /// Maximize the window containing this tab.
+    pub async fn maximize(&self) -> Result<WindowRect, WebdriverError> {
+        self.run(resize_window(&self.session_id, "maximize")).await
+    }
+
+    /// Minimize (iconify) the window containing this tab.
+    pub async fn minimize(&self) -> Result<WindowRect, WebdriverError> {
+        self.run(resize_window(&self.session_id, "minimize")).await
+    }
+
+    /// Make the window containing this tab fullscreen.
+    pub async fn fullscreen(&self) -> Result<WindowRect, WebdriverError> {
+        self.run(resize_window(&self.session_id, "fullscreen")).await
+    }
+
+    /// Take a screenshot of the visible part of the page.
+    pub async fn screenshot(&self) -> Result<Screenshot, WebdriverError> {
+        Ok(Screenshot::new(self.run(take_screenshot(&self.session_id)).await?))
+    }
+
+    /// Print the page to PDF and return the content of the PDF file.
+    pub async fn print_pdf(&self, options: &PrintOptions) -> Result<Vec<u8>, WebdriverError> {
+        self.run(print_page(&self.session_id, options)).await
+    }
+
+    /// Close the tab.
+    /// It is still listed in the [tabs](struct.AsyncSession.html#structfield.tabs) of the session.
+    pub async fn close(&self) -> Result<(), WebdriverError> {
+        self.run(close_active_tab(&self.session_id)).await
+    }
+}
+
+impl PartialEq for AsyncTab {
+    fn eq(&self, other: &Self) -> bool {
+        self.get_id() == other.get_id()
+    }
+}
+
+impl WebdriverObject for AsyncTab {
+    fn get_id(&self) -> &String {
+        &self.id
+    }
+}
+
+impl fmt::Debug for AsyncTab {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AsyncTab")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+/// The async equivalent of an [Element](../elements/struct.Element.html).
+#[derive(Clone)]
+pub struct AsyncElement {
+    id: String,
+    session_id: Arc<String>,
+    tab_id: Arc<String>,
+    client: Arc<AsyncClient>
+}
+
+impl AsyncElement {
+    async fn run<T>(&self, command: Command<T>) -> Result<T, WebdriverError> {
+        self.client.run_in_tab(&self.session_id, &self.tab_id, command).await
+    }
+
+    fn element(&self, id: String) -> AsyncElement {
+        AsyncElement {
+            id,
+            session_id: Arc::clone(&self.session_id),
+            tab_id: Arc::clone(&self.tab_id),
+            client: Arc::clone(&self.client)
+        }
+    }
+
+    /// Find an element inside this element, selected by a [Selector](../enums/enum.Selector.html).
+    pub async fn find(&self, selector: Selector, tofind: &str) -> Result<Option<AsyncElement>, WebdriverError> {
+        match self.run(find_element_from_element(&self.session_id, &self.id, selector, tofind)).await {
+            Ok(id) => Ok(Some(self.element(id))),
+            Err(error) if error == ErrorKind::NoSuchElement => Ok(None),
+            Err(error) => Err(error)
+        }
+    }
+
+    /// Find every element inside this element matching a [Selector](../enums/enum.Selector.html).
+    pub async fn find_all(&self, selector: Selector, tofind: &str) -> Result<Vec<AsyncElement>, WebdriverError> {
+        let ids = self.run(find_elements_from_element(&self.session_id, &self.id, selector, tofind)).await?;
+        Ok(ids.into_iter().map(|id| self.element(id)).collect())
+    }
+
+    /// Type a text in the element. It can contain special [Keys](../keys/enum.Keys.html).
+    pub async fn type_text(&self, text: &str) -> Result<(), WebdriverError> {
+        self.run(send_text_to_element(&self.session_id, &self.id, text)).await
+    }
+
+    pub async fn get_text(&self) -> Result<String, WebdriverError> {
+        self.run(get_element_text(&self.session_id, &self.id)).await
+    }
+
+    pub async fn get_attribute(&self, attribute_name: &str) -> Result<String, WebdriverError> {
+        self.run(get_element_attribute(&self.session_id, &self.id, attribute_name)).await
+    }
+
+    pub async fn get_tag_name(&self) -> Result<String, WebdriverError> {
+        self.run(get_element_tag_name(&self.session_id, &self.id)).await
+    }
+
+    pub async fn get_css_value(&self, property_name: &str) -> Result<String, WebdriverError> {
+        self.run(get_element_css_value(&self.session_id, &self.id, property_name)).await
+    }
+
+    pub async fn get_property(&self, property_name: &str) -> Result<String, WebdriverError> {
+        self.run(get_element_property(&self.session_id, &self.id, property_name)).await
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub async fn get_rect(&self) -> Result<((usize, usize), (usize, usize)), WebdriverError> {
+        self.run(get_element_rect(&self.session_id, &self.id)).await
+    }
+
+    pub async fn is_enabled(&self) -> Result<bool, WebdriverError> {
+        self.run(is_element_enabled(&self.session_id, &self.id)).await
+    }
+
+    /// Check if the element is visible, with the webdriver's definition of visibility.
+    pub async fn is_displayed(&self) -> Result<bool, WebdriverError> {
+        self.run(is_element_displayed(&self.session_id, &self.id)).await
+    }
+
+    /// Click on the element, like [Element::click()](../elements/struct.Element.html#method.click).
+    pub async fn click(&self) -> Result<(), WebdriverError> {
+        if self.run(execute_script_sync(&self.session_id, "arguments[0].click();", vec![self.as_json_object()])).await.is_ok() {
+            return Ok(());
+        } else {
+            error!("Failed to click with javascript. Using normal method.");
+        }
+
+        match self.run(click_on_element(&self.session_id, &self.id)).await {
+            Ok(()) => Ok(()),
+            Err(error) if error == ErrorKind::ElementNotInteractable || error == ErrorKind::ElementClickIntercepted => Ok(()),
+            Err(error) => Err(error)
+        }
+    }
+
+    /// Take a screenshot of the element.
+    pub async fn screenshot(&self) -> Result<Screenshot, WebdriverError> {
+        Ok(Screenshot::new(self.run(take_element_screenshot(&self.session_id, &self.id)).await?))
+    }
+
+    pub fn as_json_object(&self) -> JsonValue {
+        object!{ "element-6066-11e4-a52e-4f735466cecf" => self.id.as_str() }
+    }
+
+    pub async fn scroll_into_view(&self) -> Result<(), WebdriverError> {
+        self.run(execute_script_sync(&self.session_id, "arguments[0].scrollIntoView();", vec![self.as_json_object()])).await?;
+        Ok(())
+    }
+}
+
+impl PartialEq for AsyncElement {
+    fn eq(&self, other: &Self) -> bool {
+        self.get_id() == other.get_id()
+    }
+}
+
+impl WebdriverObject for AsyncElement {
+    fn get_id(&self) -> &String {
+        &self.id
+    }
+}
+
+impl From<AsyncElement> for ElementRef {
+    fn from(element: AsyncElement) -> Self {
+        ElementRef::from(&element)
+    }
+}
+
+impl fmt::Debug for AsyncElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AsyncElement")
+            .field("id", &self.id)
+            .field("tab_id", &self.tab_id)
+            .finish()
+    }
+}
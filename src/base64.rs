@@ -0,0 +1,69 @@
+//! Minimal base64 decoding, used to read screenshots and PDFs sent by the webdriver
+
+/// Decode standard base64 (with or without padding).
+/// Whitespace is ignored. Return `None` if the input is not valid base64.
+pub(crate) fn decode(input: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    let mut padding = 0;
+
+    for byte in input.bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => {
+                padding += 1;
+                continue;
+            },
+            b' ' | b'\n' | b'\r' | b'\t' => continue,
+            _ => return None
+        };
+        if padding > 0 {
+            // data after padding
+            return None;
+        }
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    // a single remaining character cannot encode a byte
+    if bits >= 6 || padding > 2 {
+        return None;
+    }
+
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode;
+
+    #[test]
+    fn valid() {
+        assert_eq!(decode(""), Some(Vec::new()));
+        assert_eq!(decode("Zg=="), Some(b"f".to_vec()));
+        assert_eq!(decode("Zm8="), Some(b"fo".to_vec()));
+        assert_eq!(decode("Zm9v"), Some(b"foo".to_vec()));
+        assert_eq!(decode("Zg"), Some(b"f".to_vec()));
+        assert_eq!(decode("Zm9v\r\nYmFy"), Some(b"foobar".to_vec()));
+        assert_eq!(decode("+/+/"), Some(vec![0xfb, 0xff, 0xbf]));
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(decode("Zm9v!"), None);
+        assert_eq!(decode("Z"), None);
+        assert_eq!(decode("Zm9vY"), None);
+        assert_eq!(decode("Zg==Zg=="), None);
+        assert_eq!(decode("Zg==="), None);
+    }
+}
@@ -0,0 +1,212 @@
+//! Capabilities describe the browser a session should be created with
+
+use json::*;
+use crate::enums::Browser;
+use crate::timeouts::Timeouts;
+
+/// When a navigation is considered complete.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub enum PageLoadStrategy {
+    /// Return as soon as the navigation has been started.
+    None,
+    /// Wait for the DOM to be loaded, but not for images and stylesheets.
+    Eager,
+    /// Wait for the page to be fully loaded.
+    Normal
+}
+
+impl PageLoadStrategy {
+    pub fn to_string(self) -> &'static str {
+        match self {
+            PageLoadStrategy::None => "none",
+            PageLoadStrategy::Eager => "eager",
+            PageLoadStrategy::Normal => "normal"
+        }
+    }
+}
+
+/// What the browser does when a user prompt is open and a command is received.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub enum UnhandledPromptBehavior {
+    Dismiss,
+    Accept,
+    DismissAndNotify,
+    AcceptAndNotify,
+    Ignore
+}
+
+impl UnhandledPromptBehavior {
+    pub fn to_string(self) -> &'static str {
+        match self {
+            UnhandledPromptBehavior::Dismiss => "dismiss",
+            UnhandledPromptBehavior::Accept => "accept",
+            UnhandledPromptBehavior::DismissAndNotify => "dismiss and notify",
+            UnhandledPromptBehavior::AcceptAndNotify => "accept and notify",
+            UnhandledPromptBehavior::Ignore => "ignore"
+        }
+    }
+}
+
+/// The proxy configuration of the browser.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub enum Proxy {
+    /// Direct connection, no proxy.
+    Direct,
+    /// Use the proxy settings of the system.
+    System,
+    /// Detect the proxy to use.
+    Autodetect,
+    /// Use a proxy auto-config file located at this url.
+    Pac(String),
+    /// Use the given proxies. Hosts are formatted as `host:port`.
+    Manual {
+        ftp: Option<String>,
+        http: Option<String>,
+        ssl: Option<String>,
+        socks: Option<String>,
+        socks_version: Option<u8>,
+        /// Hosts that must be reached without proxy.
+        no_proxy: Vec<String>
+    }
+}
+
+impl Proxy {
+    pub fn to_json(&self) -> JsonValue {
+        match self {
+            Proxy::Direct => object!{ "proxyType" => "direct" },
+            Proxy::System => object!{ "proxyType" => "system" },
+            Proxy::Autodetect => object!{ "proxyType" => "autodetect" },
+            Proxy::Pac(url) => object!{ "proxyType" => "pac", "proxyAutoconfigUrl" => url.as_str() },
+            Proxy::Manual{ftp, http, ssl, socks, socks_version, no_proxy} => {
+                let mut proxy = object!{ "proxyType" => "manual" };
+                if let Some(ftp) = ftp {
+                    proxy["ftpProxy"] = ftp.as_str().into();
+                }
+                if let Some(http) = http {
+                    proxy["httpProxy"] = http.as_str().into();
+                }
+                if let Some(ssl) = ssl {
+                    proxy["sslProxy"] = ssl.as_str().into();
+                }
+                if let Some(socks) = socks {
+                    proxy["socksProxy"] = socks.as_str().into();
+                }
+                if let Some(socks_version) = socks_version {
+                    proxy["socksVersion"] = (*socks_version).into();
+                }
+                if !no_proxy.is_empty() {
+                    proxy["noProxy"] = no_proxy.clone().into();
+                }
+                proxy
+            }
+        }
+    }
+}
+
+/// A set of [W3C capabilities](https://www.w3.org/TR/webdriver/#capabilities).
+/// Every field is optional and unset fields are not sent to the webdriver.
+/// Browser specific options can be set with [set_extension()](#method.set_extension).
+///
+/// # Example
+///
+/// ```rust
+/// use lw_webdriver::{capabilities::{Capabilities, PageLoadStrategy}, enums::Browser};
+/// use json::object;
+///
+/// let mut capabilities = Capabilities::new();
+/// capabilities.browser_name = Some(Browser::Firefox);
+/// capabilities.page_load_strategy = Some(PageLoadStrategy::Eager);
+/// capabilities.set_extension("moz:firefoxOptions", object!{ "prefs" => object!{ "dom.webnotifications.enabled" => false } });
+///
+/// assert_eq!(capabilities.to_json()["pageLoadStrategy"], "eager");
+/// ```
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct Capabilities {
+    pub browser_name: Option<Browser>,
+    pub browser_version: Option<String>,
+    pub platform_name: Option<String>,
+    pub accept_insecure_certs: Option<bool>,
+    pub page_load_strategy: Option<PageLoadStrategy>,
+    pub proxy: Option<Proxy>,
+    pub timeouts: Option<Timeouts>,
+    pub unhandled_prompt_behavior: Option<UnhandledPromptBehavior>,
+    pub strict_file_interactability: Option<bool>,
+    /// Vendor specific capabilities, like `moz:firefoxOptions` or `goog:chromeOptions`.
+    pub extensions: Vec<(String, JsonValue)>
+}
+
+impl Capabilities {
+    /// Create an empty set of capabilities.
+    pub fn new() -> Self {
+        Capabilities::default()
+    }
+
+    /// Set a vendor specific capability.
+    /// The key must contain a colon (like `moz:firefoxOptions`) to be accepted by the webdriver.
+    /// If the capability is already set, it is replaced.
+    pub fn set_extension(&mut self, key: &str, value: JsonValue) {
+        match self.extensions.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value,
+            None => self.extensions.push((key.to_string(), value))
+        }
+    }
+
+    /// Get a vendor specific capability.
+    pub fn get_extension(&self, key: &str) -> Option<&JsonValue> {
+        self.extensions.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Get a vendor specific capability, inserting an empty object if it is not set.
+    pub(crate) fn get_extension_mut(&mut self, key: &str) -> &mut JsonValue {
+        if let Some(position) = self.extensions.iter().position(|(k, _)| k == key) {
+            &mut self.extensions[position].1
+        } else {
+            self.extensions.push((key.to_string(), object!{}));
+            &mut self.extensions.last_mut().unwrap().1
+        }
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let mut capabilities = object!{};
+        if let Some(browser_name) = self.browser_name {
+            capabilities["browserName"] = browser_name.to_string().into();
+        }
+        if let Some(browser_version) = &self.browser_version {
+            capabilities["browserVersion"] = browser_version.as_str().into();
+        }
+        if let Some(platform_name) = &self.platform_name {
+            capabilities["platformName"] = platform_name.as_str().into();
+        }
+        if let Some(accept_insecure_certs) = self.accept_insecure_certs {
+            capabilities["acceptInsecureCerts"] = accept_insecure_certs.into();
+        }
+        if let Some(page_load_strategy) = self.page_load_strategy {
+            capabilities["pageLoadStrategy"] = page_load_strategy.to_string().into();
+        }
+        if let Some(proxy) = &self.proxy {
+            capabilities["proxy"] = proxy.to_json();
+        }
+        if let Some(timeouts) = &self.timeouts {
+            capabilities["timeouts"] = timeouts.to_json();
+        }
+        if let Some(unhandled_prompt_behavior) = self.unhandled_prompt_behavior {
+            capabilities["unhandledPromptBehavior"] = unhandled_prompt_behavior.to_string().into();
+        }
+        if let Some(strict_file_interactability) = self.strict_file_interactability {
+            capabilities["strictFileInteractability"] = strict_file_interactability.into();
+        }
+        for (key, value) in &self.extensions {
+            capabilities[key.as_str()] = value.clone();
+        }
+        capabilities
+    }
+}
@@ -0,0 +1,265 @@
+//! Record the requests sent to a webdriver and replay them later without a browser
+
+use json::*;
+use std::{fs, io};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::result::Result;
+use std::sync::Mutex;
+use log::{debug, error};
+use crate::transport::{Transport, Request, Response, Method};
+
+/// A request and the response of the webdriver (or the error of the transport).
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Interaction {
+    pub method: Method,
+    /// The path of the url, like `/session/<id>/url`.
+    pub path: String,
+    pub body: Option<String>,
+    pub response: Result<Response, String>
+}
+
+impl Interaction {
+    /// Read an interaction of a cassette.
+    /// Return `None` if a field is missing.
+    pub fn from_json(json: &JsonValue) -> Option<Interaction> {
+        let request = &json["request"];
+        let response = &json["response"];
+        Some(Interaction {
+            method: Method::from(request["method"].as_str()?)?,
+            path: request["path"].as_str()?.to_string(),
+            body: get_body(request),
+            response: match response["error"].as_str() {
+                Some(error) => Err(error.to_string()),
+                None => Ok(Response {
+                    status: response["status"].as_u16()?,
+                    body: get_body(response)?.into_bytes()
+                })
+            }
+        })
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let mut request = object!{
+            "method" => self.method.to_string(),
+            "path" => self.path.as_str()
+        };
+        if let Some(body) = &self.body {
+            set_body(&mut request, body.as_bytes());
+        }
+        let response = match &self.response {
+            Ok(response) => {
+                let mut json = object!{ "status" => response.status };
+                set_body(&mut json, &response.body);
+                json
+            },
+            Err(error) => object!{
+                "error" => error.as_str()
+            }
+        };
+        object!{
+            "request" => request,
+            "response" => response
+        }
+    }
+}
+
+/// Bodies are stored as JSON when possible, so that cassettes are readable
+fn set_body(json: &mut JsonValue, body: &[u8]) {
+    let text = String::from_utf8_lossy(body);
+    match json::parse(&text) {
+        Ok(body) => json["body"] = body,
+        Err(_) => json["bodyText"] = text.into_owned().into()
+    }
+}
+
+fn get_body(json: &JsonValue) -> Option<String> {
+    match (&json["body"], json["bodyText"].as_str()) {
+        (JsonValue::Null, Some(text)) => Some(text.to_string()),
+        (JsonValue::Null, None) => None,
+        (body, _) => Some(body.dump())
+    }
+}
+
+/// A list of interactions with a webdriver, saved as a JSON file.
+/// This is a transcript of the protocol which can be attached to bug reports.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>
+}
+
+impl Cassette {
+    pub fn new() -> Self {
+        Cassette::default()
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        object!{
+            "interactions" => self.interactions.iter().map(|i| i.to_json()).collect::<Vec<JsonValue>>()
+        }
+    }
+
+    pub fn from_json(json: &JsonValue) -> Result<Self, io::Error> {
+        if !json["interactions"].is_array() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "expected an array of interactions"));
+        }
+        let mut interactions = Vec::new();
+        for (index, interaction) in json["interactions"].members().enumerate() {
+            match Interaction::from_json(interaction) {
+                Some(interaction) => interactions.push(interaction),
+                None => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("interaction {} is invalid", index)))
+            }
+        }
+        Ok(Cassette {
+            interactions
+        })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        fs::write(path, self.to_json().pretty(2))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        let text = fs::read_to_string(path)?;
+        let json = json::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Cassette::from_json(&json)
+    }
+}
+
+/// Extract the path of an url
+fn url_path(url: &str) -> &str {
+    let start = url.find("://").map(|p| p + 3).unwrap_or(0);
+    match url[start..].find('/') {
+        Some(position) => &url[start + position..],
+        None => "/"
+    }
+}
+
+/// A [Transport](../transport/trait.Transport.html) recording every interaction, and saving them to a cassette file when it is dropped
+/// (when the session and all its tabs are dropped).
+/// An error while saving is logged and does not change the responses of the requests.
+///
+/// # Example
+///
+/// ```rust
+/// use lw_webdriver::{session::SessionBuilder, enums::Browser};
+/// use lw_webdriver::transport::MinreqTransport;
+/// use lw_webdriver::cassette::{RecordingTransport, ReplayTransport};
+///
+/// let path = std::env::temp_dir().join("example.json");
+///
+/// // record once with a real browser
+/// let mut session = SessionBuilder::new(Browser::Firefox)
+///     .transport(RecordingTransport::new(MinreqTransport, &path))
+///     .build()
+///     .unwrap();
+/// session.tabs[0].navigate("http://example.com/").unwrap();
+/// assert_eq!(session.tabs[0].get_title().unwrap(), "Example Domain");
+/// session.quit().unwrap();
+///
+/// // then replay without a browser
+/// let mut session = SessionBuilder::new(Browser::Firefox)
+///     .transport(ReplayTransport::load(&path).unwrap())
+///     .build()
+///     .unwrap();
+/// session.tabs[0].navigate("http://example.com/").unwrap();
+/// assert_eq!(session.tabs[0].get_title().unwrap(), "Example Domain");
+/// session.quit().unwrap();
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct RecordingTransport<T: Transport> {
+    transport: T,
+    path: PathBuf,
+    cassette: Mutex<Cassette>
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    /// Send requests with another transport and write them to a file (which is overwritten).
+    pub fn new<P: AsRef<Path>>(transport: T, path: P) -> Self {
+        RecordingTransport {
+            transport,
+            path: path.as_ref().to_path_buf(),
+            cassette: Mutex::new(Cassette::new())
+        }
+    }
+
+    /// Write the interactions recorded so far to the file.
+    /// This is done automatically when the transport is dropped.
+    pub fn save(&self) -> Result<(), io::Error> {
+        self.cassette.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).save(&self.path)
+    }
+}
+
+impl<T: Transport> Drop for RecordingTransport<T> {
+    fn drop(&mut self) {
+        if let Err(e) = self.save() {
+            error!("failed to save the cassette {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    fn send(&self, request: &Request) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        let response = self.transport.send(request);
+
+        let mut cassette = self.cassette.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        cassette.interactions.push(Interaction {
+            method: request.method,
+            path: url_path(&request.url).to_string(),
+            body: request.body.clone(),
+            response: match &response {
+                Ok(response) => Ok(response.clone()),
+                Err(error) => Err(error.to_string())
+            }
+        });
+
+        response
+    }
+}
+
+/// A [Transport](../transport/trait.Transport.html) answering requests with the responses of a cassette, in order.
+/// Requests are matched by method and path (bodies are not compared).
+/// An unexpected request fails with a [FailedRequest](../error/enum.ErrorKind.html#variant.FailedRequest) error.
+pub struct ReplayTransport {
+    interactions: Mutex<std::vec::IntoIter<Interaction>>
+}
+
+impl ReplayTransport {
+    pub fn new(cassette: Cassette) -> Self {
+        ReplayTransport {
+            interactions: Mutex::new(cassette.interactions.into_iter())
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        Ok(ReplayTransport::new(Cassette::load(path)?))
+    }
+
+    /// Return how many interactions of the cassette were not replayed.
+    /// Give the transport to the session in an `Arc` to call this after the session is built.
+    pub fn remaining(&self) -> usize {
+        self.interactions.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).len()
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send(&self, request: &Request) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        let path = url_path(&request.url);
+        let interaction = match self.interactions.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).next() {
+            Some(interaction) => interaction,
+            None => return Err(format!("the cassette has no interaction left for {} {}", request.method.to_string(), path).into())
+        };
+        if interaction.method != request.method || interaction.path != path {
+            return Err(format!("expected {} {} but got {} {}",
+                interaction.method.to_string(), interaction.path, request.method.to_string(), path).into());
+        }
+        debug!("replaying {} {}", interaction.method.to_string(), interaction.path);
+
+        interaction.response.map_err(|error| error.into())
+    }
+}
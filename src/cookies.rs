@@ -0,0 +1,369 @@
+//! Cookies can be read and written in tabs
+
+use json::*;
+use std::{fs, io};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::result::Result;
+use crate::tab::Tab;
+use crate::error::WebdriverError;
+use log::debug;
+
+/// Whether a cookie is sent with cross-site requests.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None
+}
+
+impl SameSite {
+    pub fn to_string(self) -> &'static str {
+        match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None"
+        }
+    }
+
+    pub fn from(value: &str) -> Option<SameSite> {
+        match value.to_ascii_lowercase().as_str() {
+            "strict" => Some(SameSite::Strict),
+            "lax" => Some(SameSite::Lax),
+            "none" => Some(SameSite::None),
+            _ => None
+        }
+    }
+}
+
+/// A [W3C cookie](https://www.w3.org/TR/webdriver/#cookies).
+/// Only the name and the value are required.
+///
+/// # Example
+///
+/// ```rust
+/// use lw_webdriver::cookies::{Cookie, SameSite};
+///
+/// let cookie = Cookie::new("session", "b9f3e2")
+///     .domain("example.com")
+///     .path("/")
+///     .secure(true)
+///     .http_only(true)
+///     .same_site(SameSite::Lax);
+///
+/// // a cookie without expiry is a session cookie
+/// assert_eq!(cookie.expiry, None);
+/// ```
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub path: Option<String>,
+    pub domain: Option<String>,
+    pub secure: bool,
+    pub http_only: bool,
+    /// Expiration date in seconds since the Unix Epoch. `None` for session cookies.
+    pub expiry: Option<u64>,
+    pub same_site: Option<SameSite>
+}
+
+impl Cookie {
+    /// Create a session cookie.
+    pub fn new(name: &str, value: &str) -> Self {
+        Cookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            path: None,
+            domain: None,
+            secure: false,
+            http_only: false,
+            expiry: None,
+            same_site: None
+        }
+    }
+
+    pub fn path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn domain(mut self, domain: &str) -> Self {
+        self.domain = Some(domain.to_string());
+        self
+    }
+
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
+
+    /// Set the expiration date in seconds since the Unix Epoch.
+    pub fn expiry(mut self, expiry: u64) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    /// Read a cookie sent by the webdriver.
+    /// Return `None` if the name or the value is missing.
+    pub fn from_json(json: &JsonValue) -> Option<Cookie> {
+        Some(Cookie {
+            name: json["name"].as_str()?.to_string(),
+            value: json["value"].as_str()?.to_string(),
+            path: json["path"].as_str().map(|p| p.to_string()),
+            domain: json["domain"].as_str().map(|d| d.to_string()),
+            secure: json["secure"].as_bool().unwrap_or(false),
+            http_only: json["httpOnly"].as_bool().unwrap_or(false),
+            expiry: json["expiry"].as_u64(),
+            same_site: json["sameSite"].as_str().and_then(SameSite::from)
+        })
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let mut cookie = object!{
+            "name" => self.name.as_str(),
+            "value" => self.value.as_str(),
+            "secure" => self.secure,
+            "httpOnly" => self.http_only
+        };
+        if let Some(path) = &self.path {
+            cookie["path"] = path.as_str().into();
+        }
+        if let Some(domain) = &self.domain {
+            cookie["domain"] = domain.as_str().into();
+        }
+        if let Some(expiry) = self.expiry {
+            cookie["expiry"] = expiry.into();
+        }
+        if let Some(same_site) = self.same_site {
+            cookie["sameSite"] = same_site.to_string().into();
+        }
+        cookie
+    }
+}
+
+/// A set of cookies that can be saved to a file and restored in a tab later.
+/// This is useful to log in once and reuse the authenticated state.
+/// Two formats are supported: Netscape `cookies.txt` (used by curl and wget) and JSON (the W3C representation of cookies).
+///
+/// # Example
+///
+/// ```rust
+/// # use lw_webdriver::{session::Session, enums::Browser, cookies::CookieJar};
+/// let mut session = Session::new(Browser::Firefox, false).unwrap();
+///
+/// // log in and save the cookies
+/// session.tabs[0].navigate("https://mubelotix.dev/").unwrap();
+/// let jar = CookieJar::from_tab(&session.tabs[0]).unwrap();
+/// jar.save_netscape("cookies.txt").unwrap();
+///
+/// // later, restore them (only cookies matching the domain of the page are set)
+/// let jar = CookieJar::load_netscape("cookies.txt").unwrap();
+/// session.tabs[0].navigate("https://mubelotix.dev/").unwrap();
+/// jar.restore(&session.tabs[0]).unwrap();
+/// # std::fs::remove_file("cookies.txt").unwrap();
+/// ```
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct CookieJar {
+    pub cookies: Vec<Cookie>
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        CookieJar::default()
+    }
+
+    pub fn from_cookies(cookies: Vec<Cookie>) -> Self {
+        CookieJar {
+            cookies
+        }
+    }
+
+    /// Collect every cookie visible by the current page of a tab.
+    pub fn from_tab(tab: &Tab) -> Result<Self, WebdriverError> {
+        Ok(CookieJar::from_cookies(tab.get_cookies()?))
+    }
+
+    /// Return the cookies that would be sent to this host.
+    /// Cookies whose domain starts with a dot are also sent to subdomains, the others only to this exact host.
+    /// Cookies without a domain always match, because they are set on the current page by [restore()](#method.restore).
+    pub fn cookies_for_domain(&self, host: &str) -> Vec<&Cookie> {
+        self.cookies.iter().filter(|cookie| domain_matches(cookie.domain.as_deref(), host)).collect()
+    }
+
+    /// Set the cookies matching the domain of the current page of a tab.
+    /// Other cookies are skipped because the webdriver would reject them, and so are expired cookies.
+    /// Return how many cookies were set.
+    pub fn restore(&self, tab: &Tab) -> Result<usize, WebdriverError> {
+        let url = tab.get_url()?;
+        let host = url_host(&url);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+        let mut count = 0;
+        for cookie in self.cookies_for_domain(host) {
+            if let Some(expiry) = cookie.expiry {
+                if expiry <= now {
+                    debug!("skipping expired cookie {}", cookie.name);
+                    continue;
+                }
+            }
+            tab.set_cookie(cookie)?;
+            count += 1;
+        }
+        debug!("{} cookies restored on {}", count, host);
+
+        Ok(count)
+    }
+
+    /// Serialize the cookies in the Netscape `cookies.txt` format.
+    pub fn to_netscape(&self) -> String {
+        let mut text = String::from("# Netscape HTTP Cookie File\n");
+        for cookie in &self.cookies {
+            let domain = cookie.domain.as_deref().unwrap_or("");
+            text.push_str(&format!(
+                "{}{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                if cookie.http_only { "#HttpOnly_" } else { "" },
+                domain,
+                if domain.starts_with('.') { "TRUE" } else { "FALSE" },
+                cookie.path.as_deref().unwrap_or("/"),
+                if cookie.secure { "TRUE" } else { "FALSE" },
+                cookie.expiry.unwrap_or(0),
+                cookie.name,
+                cookie.value
+            ));
+        }
+        text
+    }
+
+    /// Parse cookies in the Netscape `cookies.txt` format.
+    /// An expiry of 0 means a session cookie. The `SameSite` attribute is not part of this format.
+    pub fn from_netscape(text: &str) -> Result<Self, io::Error> {
+        let mut cookies = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+                Some(line) => (line, true),
+                None => (line, false)
+            };
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 7 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("line {}: expected 7 fields, found {}", number + 1, fields.len())));
+            }
+            let expiry: u64 = fields[4].parse().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: invalid expiry {}", number + 1, fields[4]))
+            })?;
+
+            let mut cookie = Cookie::new(fields[5], fields[6])
+                .path(fields[2])
+                .secure(fields[3].eq_ignore_ascii_case("TRUE"))
+                .http_only(http_only);
+            if fields[1].eq_ignore_ascii_case("TRUE") && !fields[0].starts_with('.') {
+                // the leading dot is what makes a cookie match subdomains
+                cookie.domain = Some(format!(".{}", fields[0]));
+            } else if !fields[0].is_empty() {
+                cookie.domain = Some(fields[0].to_string());
+            }
+            if expiry != 0 {
+                cookie.expiry = Some(expiry);
+            }
+            cookies.push(cookie);
+        }
+
+        Ok(CookieJar::from_cookies(cookies))
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        self.cookies.iter().map(|cookie| cookie.to_json()).collect::<Vec<JsonValue>>().into()
+    }
+
+    /// Parse cookies from a JSON array of [W3C cookies](https://www.w3.org/TR/webdriver/#cookies).
+    pub fn from_json(json: &JsonValue) -> Result<Self, io::Error> {
+        if !json.is_array() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "expected an array of cookies"));
+        }
+        let mut cookies = Vec::new();
+        for (index, cookie) in json.members().enumerate() {
+            match Cookie::from_json(cookie) {
+                Some(cookie) => cookies.push(cookie),
+                None => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("cookie {} has no name or value", index)))
+            }
+        }
+
+        Ok(CookieJar::from_cookies(cookies))
+    }
+
+    pub fn save_netscape<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        fs::write(path, self.to_netscape())
+    }
+
+    pub fn load_netscape<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        CookieJar::from_netscape(&fs::read_to_string(path)?)
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        fs::write(path, self.to_json().pretty(2))
+    }
+
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        let text = fs::read_to_string(path)?;
+        let json = json::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        CookieJar::from_json(&json)
+    }
+}
+
+/// Extract the host of an url
+pub(crate) fn url_host(url: &str) -> &str {
+    let url = match url.find("://") {
+        Some(position) => &url[position + 3..],
+        None => url
+    };
+    let url = &url[..url.find(['/', '?', '#']).unwrap_or(url.len())];
+    let url = match url.rfind('@') {
+        Some(position) => &url[position + 1..],
+        None => url
+    };
+    if url.starts_with('[') {
+        // IPv6 address
+        return &url[..url.find(']').map(|p| p + 1).unwrap_or(url.len())];
+    }
+    &url[..url.find(':').unwrap_or(url.len())]
+}
+
+/// Check if a cookie of this domain is sent to this host.
+/// Only domains with a leading dot match subdomains, like the include subdomains flag of the Netscape format.
+pub(crate) fn domain_matches(domain: Option<&str>, host: &str) -> bool {
+    let domain = match domain {
+        Some(domain) => domain,
+        // the webdriver makes it a host-only cookie of the current page
+        None => return true
+    };
+    match domain.strip_prefix('.') {
+        Some(domain) => {
+            let (host, domain) = (host.as_bytes(), domain.as_bytes());
+            host.eq_ignore_ascii_case(domain)
+                || (host.len() > domain.len()
+                    && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain)
+                    && host[host.len() - domain.len() - 1] == b'.')
+        },
+        None => host.eq_ignore_ascii_case(domain)
+    }
+}
@@ -1,110 +1,166 @@
-use crate::tab::*;
 use crate::error::*;
 use crate::enums::{Selector, WebdriverObject};
+use crate::screenshot::Screenshot;
+use crate::actions::*;
 use json::*;
 use std::result::Result;
-use log::{debug, info, warn, error};
-use std::rc::Rc;
-use crate::http_requests::{execute_script_sync, click_on_element, get_element_text, send_text_to_element, get_selected_tab, select_tab,
-    get_element_attribute, get_element_css_value, get_element_property, get_element_tag_name, is_element_enabled, get_element_rect};
-
+use log::{warn, error};
+use std::sync::Arc;
+use std::fmt;
+use std::time::Duration;
+use crate::http_requests::{Client, Command, execute_script_sync, click_on_element, get_element_text, send_text_to_element,
+    get_element_attribute, get_element_css_value, get_element_property, get_element_tag_name, is_element_enabled, is_element_displayed, get_element_rect,
+    find_element_from_element, find_elements_from_element, take_element_screenshot,
+    perform_actions};
+
+#[derive(Clone)]
 pub struct Element {
     id: String,
-    session_id: Rc<String>,
-    tab_id: Rc<String>
+    session_id: Arc<String>,
+    tab_id: Arc<String>,
+    client: Arc<Client>
 }
 
 impl Element {
-    pub fn new(id: String, session_id: Rc<String>, tab_id: Rc<String>) -> Self {
+    pub(crate) fn new(id: String, session_id: Arc<String>, tab_id: Arc<String>, client: Arc<Client>) -> Self {
         Element{
             id,
             session_id,
-            tab_id
+            tab_id,
+            client
         }
     }
 
-    fn select_tab(&self) -> Result<(), WebdriverError> {
-        // check if it is needed to select the tab
-        if let Ok(id) = get_selected_tab(&self.session_id) {
-            if id == *self.tab_id {
-                return Ok(());
+    /// Select the tab of the element and send a command to it, without letting other threads select another tab in between
+    fn run<T>(&self, command: Command<T>) -> Result<T, WebdriverError> {
+        self.client.run_in_tab(&self.session_id, &self.tab_id, command)
+    }
+
+    /// Find an element inside this element, selected by a [Selector](../enums/enum.Selector.html).
+    /// Like [Tab::find()](../tab/struct.Tab.html#method.find), return `None` if no element is found.
+    /// XPath selectors must start with `.` to be relative to this element.
+    pub fn find(&self, selector: Selector, tofind: &str) -> Result<Option<Element>, WebdriverError> {
+        match self.run(find_element_from_element(&self.session_id, &self.id, selector, tofind)) {
+            Ok(id) => {
+                Ok(Some(Element::new(id, Arc::clone(&self.session_id), Arc::clone(&self.tab_id), Arc::clone(&self.client))))
+            },
+            Err(error) if error == ErrorKind::NoSuchElement => {
+                Ok(None)
+            },
+            Err(error) => {
+                Err(error)
             }
         }
+    }
 
-        // select tab
-        select_tab(&self.session_id, &self.tab_id)
+    /// Find every element inside this element matching a [Selector](../enums/enum.Selector.html).
+    pub fn find_all(&self, selector: Selector, tofind: &str) -> Result<Vec<Element>, WebdriverError> {
+        let ids = self.run(find_elements_from_element(&self.session_id, &self.id, selector, tofind))?;
+        Ok(ids.into_iter().map(|id| Element::new(id, Arc::clone(&self.session_id), Arc::clone(&self.tab_id), Arc::clone(&self.client))).collect())
     }
 
-    pub fn type_text(&mut self, text: &str) -> Result<(), WebdriverError> {
-        self.select_tab()?;
-        send_text_to_element(&self.session_id, &self.id, text)
+    /// Type a text in the element. It can contain special [Keys](../keys/enum.Keys.html) and [key sequences](../keys/struct.KeySequence.html).
+    pub fn type_text(&self, text: &str) -> Result<(), WebdriverError> {
+        self.run(send_text_to_element(&self.session_id, &self.id, text))
     }
 
     pub fn get_text(&self) -> Result<String, WebdriverError> {
-        self.select_tab()?;
-        get_element_text(&self.session_id, &self.id)
+        self.run(get_element_text(&self.session_id, &self.id))
     }
 
     pub fn get_attribute(&self, attribute_name: &str) -> Result<String, WebdriverError> {
-        self.select_tab()?;
-        get_element_attribute(&self.session_id, &self.id, attribute_name)
+        self.run(get_element_attribute(&self.session_id, &self.id, attribute_name))
     }
 
     pub fn get_tag_name(&self) -> Result<String, WebdriverError> {
-        self.select_tab()?;
-        get_element_tag_name(&self.session_id, &self.id)
+        self.run(get_element_tag_name(&self.session_id, &self.id))
     }
 
     pub fn get_css_value(&self, property_name: &str) -> Result<String, WebdriverError> {
-        self.select_tab()?;
-        get_element_css_value(&self.session_id, &self.id, property_name)
+        self.run(get_element_css_value(&self.session_id, &self.id, property_name))
     }
 
     pub fn get_property(&self, property_name: &str) -> Result<String, WebdriverError> {
-        self.select_tab()?;
-        get_element_property(&self.session_id, &self.id, property_name)
+        self.run(get_element_property(&self.session_id, &self.id, property_name))
     }
 
+    #[allow(clippy::type_complexity)]
     pub fn get_rect(&self) -> Result<((usize, usize), (usize, usize)), WebdriverError> {
-        self.select_tab()?;
-        get_element_rect(&self.session_id, &self.id)
+        self.run(get_element_rect(&self.session_id, &self.id))
     }
 
     pub fn is_enabled(&self) -> Result<bool, WebdriverError> {
-        self.select_tab()?;
-        is_element_enabled(&self.session_id, &self.id)
+        self.run(is_element_enabled(&self.session_id, &self.id))
+    }
+
+    /// Return false if the element is hidden (by `display: none`, `visibility: hidden`, a zero size...).
+    pub fn is_displayed(&self) -> Result<bool, WebdriverError> {
+        self.run(is_element_displayed(&self.session_id, &self.id))
     }
 
-    pub fn click(&mut self) -> Result<(), WebdriverError> {
-        self.select_tab()?;
-        
+    pub fn click(&self) -> Result<(), WebdriverError> {
         // TODO watch the bug
         warn!("Using javascript click because of a bug in geckodriver where and error hapen but is not reported to us.");
-        if let Ok(()) = execute_script_sync(&self.session_id, "arguments[0].click();", vec![self.as_json_object()]) {
+        if self.run(execute_script_sync(&self.session_id, "arguments[0].click();", vec![self.as_json_object()])).is_ok() {
             return Ok(());
         } else {
             error!("Failed to click with javascript. Using normal method.");
         }
 
-        match click_on_element(&self.session_id, &self.id) {
+        match self.run(click_on_element(&self.session_id, &self.id)) {
             Ok(()) => {
                 Ok(())
             }
-            Err(error) if error == WebdriverError::ElementNotInteractable || error == WebdriverError::ElementClickIntercepted => {
+            Err(error) if error == ErrorKind::ElementNotInteractable || error == ErrorKind::ElementClickIntercepted => {
                 Ok(())
             },
             Err(error) => {
-                return Err(error)
+                Err(error)
             }
         }
     }
 
+    /// Take a screenshot of the element.
+    /// The element is scrolled into view first.
+    pub fn screenshot(&self) -> Result<Screenshot, WebdriverError> {
+        Ok(Screenshot::new(self.run(take_element_screenshot(&self.session_id, &self.id))?))
+    }
+
+    /// Move the mouse over the element (useful to open hover menus).
+    pub fn hover(&self) -> Result<(), WebdriverError> {
+        self.run(perform_actions(&self.session_id, &Actions::new()
+            .pointer(PointerSource::new("mouse", PointerType::Mouse).move_to_element(self))))
+    }
+
+    /// Double click on the element with the mouse.
+    pub fn double_click(&self) -> Result<(), WebdriverError> {
+        self.run(perform_actions(&self.session_id, &Actions::new()
+            .pointer(PointerSource::new("mouse", PointerType::Mouse).move_to_element(self).click(MouseButton::Left).click(MouseButton::Left))))
+    }
+
+    /// Right click on the element with the mouse (useful to open context menus).
+    pub fn right_click(&self) -> Result<(), WebdriverError> {
+        self.run(perform_actions(&self.session_id, &Actions::new()
+            .pointer(PointerSource::new("mouse", PointerType::Mouse).move_to_element(self).click(MouseButton::Right))))
+    }
+
+    /// Drag the element with the mouse and drop it on another element.
+    pub fn drag_and_drop_to(&self, target: &Element) -> Result<(), WebdriverError> {
+        self.run(perform_actions(&self.session_id, &Actions::new()
+            .pointer(PointerSource::new("mouse", PointerType::Mouse)
+                .move_to_element(self)
+                .down(MouseButton::Left)
+                .move_to(0, 0, Origin::Element(target.into()), Duration::from_millis(250))
+                .up(MouseButton::Left))))
+    }
+
     pub fn as_json_object(&self) -> JsonValue {
         object!{ "element-6066-11e4-a52e-4f735466cecf" => self.id.as_str() }
     }
 
     pub fn scroll_into_view(&self) -> Result<(), WebdriverError> {
-        execute_script_sync(&self.session_id, "arguments[0].scrollIntoView();", vec![self.as_json_object()])
+        self.run(execute_script_sync(&self.session_id, "arguments[0].scrollIntoView();", vec![self.as_json_object()]))?;
+        Ok(())
     }
 }
 
@@ -114,8 +170,54 @@ impl PartialEq for Element {
     }
 }
 
+impl fmt::Debug for Element {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Element")
+            .field("id", &self.id)
+            .field("tab_id", &self.tab_id)
+            .finish()
+    }
+}
+
 impl WebdriverObject for Element {
     fn get_id(&self) -> &String {
         &self.id
     }
-}
\ No newline at end of file
+}
+
+/// The id of an element, without the session and the tab it belongs to.
+/// It is used by [FrameId](../frames/enum.FrameId.html) and [Origin](../actions/enum.Origin.html), so that both [Elements](struct.Element.html) and async elements can be used there.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct ElementRef {
+    id: String
+}
+
+impl ElementRef {
+    pub fn as_json_object(&self) -> JsonValue {
+        object!{ "element-6066-11e4-a52e-4f735466cecf" => self.id.as_str() }
+    }
+}
+
+impl WebdriverObject for ElementRef {
+    fn get_id(&self) -> &String {
+        &self.id
+    }
+}
+
+impl<T: WebdriverObject> From<&T> for ElementRef {
+    fn from(element: &T) -> Self {
+        ElementRef {
+            id: element.get_id().clone()
+        }
+    }
+}
+
+impl From<Element> for ElementRef {
+    fn from(element: Element) -> Self {
+        ElementRef {
+            id: element.id
+        }
+    }
+}
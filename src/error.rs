@@ -1,8 +1,184 @@
+use json::JsonValue;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+/// An error returned by this crate.
+/// Use [kind](#structfield.kind) to know what went wrong.
+/// When the webdriver reported the error, the other fields contain its explanations.
+/// 
+/// ```rust
+/// use lw_webdriver::error::{WebdriverError, ErrorKind};
+/// 
+/// let error = WebdriverError::from(ErrorKind::NoSuchElement);
+/// assert_eq!(error, ErrorKind::NoSuchElement);
+/// assert_eq!(error.to_string(), "no such element");
+/// ```
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct WebdriverError {
+    pub kind: ErrorKind,
+    details: Box<ErrorDetails>
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+struct ErrorDetails {
+    message: Option<String>,
+    stacktrace: Option<String>,
+    data: Option<JsonValue>,
+    status: Option<u16>,
+    command: Option<String>,
+    driver_output: Option<String>,
+    source: Option<Arc<dyn Error + Send + Sync>>
+}
+
+impl WebdriverError {
+    /// Build an error from the `value` of a webdriver error response.
+    pub(crate) fn from_response(value: &JsonValue) -> Self {
+        let mut error = WebdriverError::from(ErrorKind::from(value["error"].to_string()));
+        error.details.message = value["message"].as_str().map(|m| m.to_string());
+        error.details.stacktrace = value["stacktrace"].as_str().filter(|s| !s.is_empty()).map(|s| s.to_string());
+        if !value["data"].is_null() {
+            error.details.data = Some(value["data"].clone());
+        }
+        error
+    }
+
+    /// The human readable message sent by the webdriver.
+    pub fn message(&self) -> Option<&str> {
+        self.details.message.as_deref()
+    }
+
+    /// The stacktrace sent by the webdriver.
+    pub fn stacktrace(&self) -> Option<&str> {
+        self.details.stacktrace.as_deref()
+    }
+
+    /// Additional data sent by the webdriver (like the text of an unexpected alert).
+    pub fn data(&self) -> Option<&JsonValue> {
+        self.details.data.as_ref()
+    }
+
+    /// The message of the dialog which caused an [UnexpectedAlertOpen](enum.ErrorKind.html#variant.UnexpectedAlertOpen) error.
+    pub fn alert_text(&self) -> Option<&str> {
+        if self.kind != ErrorKind::UnexpectedAlertOpen {
+            return None;
+        }
+        self.data()?["text"].as_str()
+    }
+
+    /// The HTTP status code of the response.
+    pub fn status(&self) -> Option<u16> {
+        self.details.status
+    }
+
+    /// The command that failed, formatted as `METHOD /path`.
+    pub fn command(&self) -> Option<&str> {
+        self.details.command.as_deref()
+    }
+
+    /// The last lines written by the webdriver process, if it was launched by a [DriverService](../service/struct.DriverService.html).
+    /// Only set on [SessionNotCreated](enum.ErrorKind.html#variant.SessionNotCreated) and [FailedRequest](enum.ErrorKind.html#variant.FailedRequest) errors.
+    pub fn driver_output(&self) -> Option<&str> {
+        self.details.driver_output.as_deref()
+    }
+
+    pub(crate) fn with_message(mut self, message: String) -> Self {
+        self.details.message = Some(message);
+        self
+    }
+
+    pub(crate) fn with_driver_output(mut self, driver_output: String) -> Self {
+        self.details.driver_output = Some(driver_output);
+        self
+    }
+
+    pub(crate) fn with_command(mut self, method: &str, path: &str) -> Self {
+        self.details.command = Some(format!("{} {}", method, path));
+        self
+    }
+
+    pub(crate) fn with_status(mut self, status: u16) -> Self {
+        self.details.status = Some(status);
+        self
+    }
+
+    pub(crate) fn with_source<E: Error + Send + Sync + 'static>(mut self, source: E) -> Self {
+        self.details.source = Some(Arc::new(source));
+        self
+    }
+
+    pub(crate) fn with_boxed_source(mut self, source: Box<dyn Error + Send + Sync>) -> Self {
+        self.details.source = Some(Arc::from(source));
+        self
+    }
+}
+
+impl From<ErrorKind> for WebdriverError {
+    fn from(kind: ErrorKind) -> Self {
+        WebdriverError {
+            kind,
+            details: Box::default()
+        }
+    }
+}
+
+impl PartialEq for WebdriverError {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && self.details.message == other.details.message
+            && self.details.stacktrace == other.details.stacktrace
+            && self.details.data == other.details.data
+            && self.details.status == other.details.status
+            && self.details.command == other.details.command
+            && self.details.driver_output == other.details.driver_output
+    }
+}
+
+impl PartialEq<ErrorKind> for WebdriverError {
+    fn eq(&self, other: &ErrorKind) -> bool {
+        self.kind == *other
+    }
+}
+
+impl fmt::Display for WebdriverError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(message) = self.message() {
+            write!(f, ": {}", message)?;
+        }
+        match (self.command(), self.status()) {
+            (Some(command), Some(status)) => write!(f, " ({}, HTTP {})", command, status)?,
+            (Some(command), None) => write!(f, " ({})", command)?,
+            (None, Some(status)) => write!(f, " (HTTP {})", status)?,
+            (None, None) => ()
+        }
+        if let Some(driver_output) = self.driver_output() {
+            write!(f, "\ndriver output:\n{}", driver_output)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for WebdriverError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.details.source {
+            Some(source) => Some(source.as_ref()),
+            None => None
+        }
+    }
+}
+
 #[derive(PartialEq)]
 #[derive(Debug)]
 #[derive(Clone)]
-pub enum WebdriverError {
+pub enum ErrorKind {
     UnsupportedPlatform,
+    DriverNotFound,
+    FailedToLaunchDriver(String),
+    DriverNotReady,
     FailedRequest,
     InvalidResponse,
     Unknow,
@@ -35,36 +211,78 @@ pub enum WebdriverError {
     Custom(String)
 }
 
-impl WebdriverError {
+impl ErrorKind {
     pub fn from(error: String) -> Self {
         match error.as_str() {
-            "element click intercepted" => WebdriverError::ElementClickIntercepted,
-            "element not interactable" => WebdriverError::ElementNotInteractable,
-            "insecure certificate" => WebdriverError::InsecureCertificate,
-            "invalid argument" => WebdriverError::InvalidArgument,
-            "invalid cookie domain" => WebdriverError::InvalidCookieDomain,
-            "invalid element state" => WebdriverError::InvalidElementState,
-            "invalid selector" => WebdriverError::InvalidSelector,
-            "invalid session id " => WebdriverError::InvalidSessionId,
-            "javascript error" => WebdriverError::JavascriptError,
-            "move target out of bounds" => WebdriverError::MoveTargetOutOfBounds,
-            "no such alert" => WebdriverError::NoSuchAlert,
-            "no such cookie" => WebdriverError::NoSuchCookie,
-            "no such element" => WebdriverError::NoSuchElement,
-            "no such frame" => WebdriverError::NoSuchFrame,
-            "no such window" => WebdriverError::NoSuchWindow,
-            "script timeout error" => WebdriverError::ScriptTimeoutError,
-            "session not created" => WebdriverError::SessionNotCreated,
-            "stale element reference" => WebdriverError::StaleElementReference,
-            "timeout" => WebdriverError::Timeout,
-            "unable to set cookie" => WebdriverError::UnnableToSetCookie,
-            "unable to capture screen" => WebdriverError::UnableToCaptureScreen,
-            "unexpected alert open" => WebdriverError::UnexpectedAlertOpen,
-            "unknown command" => WebdriverError::UnknowCommand,
-            "unknown error" => WebdriverError::Unknow,
-            "unknown method" => WebdriverError::UnknowMethod,
-            "unsupported operation" => WebdriverError::UnsupportedOperation,
-            _ => WebdriverError::Custom(error),
+            "element click intercepted" => ErrorKind::ElementClickIntercepted,
+            "element not interactable" => ErrorKind::ElementNotInteractable,
+            "insecure certificate" => ErrorKind::InsecureCertificate,
+            "invalid argument" => ErrorKind::InvalidArgument,
+            "invalid cookie domain" => ErrorKind::InvalidCookieDomain,
+            "invalid element state" => ErrorKind::InvalidElementState,
+            "invalid selector" => ErrorKind::InvalidSelector,
+            "invalid session id" => ErrorKind::InvalidSessionId,
+            "javascript error" => ErrorKind::JavascriptError,
+            "move target out of bounds" => ErrorKind::MoveTargetOutOfBounds,
+            "no such alert" => ErrorKind::NoSuchAlert,
+            "no such cookie" => ErrorKind::NoSuchCookie,
+            "no such element" => ErrorKind::NoSuchElement,
+            "no such frame" => ErrorKind::NoSuchFrame,
+            "no such window" => ErrorKind::NoSuchWindow,
+            "script timeout" | "script timeout error" => ErrorKind::ScriptTimeoutError,
+            "session not created" => ErrorKind::SessionNotCreated,
+            "stale element reference" => ErrorKind::StaleElementReference,
+            "timeout" => ErrorKind::Timeout,
+            "unable to set cookie" => ErrorKind::UnnableToSetCookie,
+            "unable to capture screen" => ErrorKind::UnableToCaptureScreen,
+            "unexpected alert open" => ErrorKind::UnexpectedAlertOpen,
+            "unknown command" => ErrorKind::UnknowCommand,
+            "unknown error" => ErrorKind::Unknow,
+            "unknown method" => ErrorKind::UnknowMethod,
+            "unsupported operation" => ErrorKind::UnsupportedOperation,
+            _ => ErrorKind::Custom(error),
         }
     }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self {
+            ErrorKind::UnsupportedPlatform => "unsupported platform",
+            ErrorKind::DriverNotFound => "driver executable not found",
+            ErrorKind::FailedToLaunchDriver(reason) => return write!(f, "failed to launch driver: {}", reason),
+            ErrorKind::DriverNotReady => "driver not ready",
+            ErrorKind::FailedRequest => "failed request",
+            ErrorKind::InvalidResponse => "invalid response",
+            ErrorKind::Unknow => "unknown error",
+            ErrorKind::ElementClickIntercepted => "element click intercepted",
+            ErrorKind::ElementNotInteractable => "element not interactable",
+            ErrorKind::InsecureCertificate => "insecure certificate",
+            ErrorKind::InvalidArgument => "invalid argument",
+            ErrorKind::InvalidCookieDomain => "invalid cookie domain",
+            ErrorKind::InvalidElementState => "invalid element state",
+            ErrorKind::InvalidSelector => "invalid selector",
+            ErrorKind::InvalidSessionId => "invalid session id",
+            ErrorKind::JavascriptError => "javascript error",
+            ErrorKind::MoveTargetOutOfBounds => "move target out of bounds",
+            ErrorKind::NoSuchAlert => "no such alert",
+            ErrorKind::NoSuchCookie => "no such cookie",
+            ErrorKind::NoSuchElement => "no such element",
+            ErrorKind::NoSuchFrame => "no such frame",
+            ErrorKind::NoSuchWindow => "no such window",
+            ErrorKind::ScriptTimeoutError => "script timeout",
+            ErrorKind::SessionNotCreated => "session not created",
+            ErrorKind::StaleElementReference => "stale element reference",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::UnnableToSetCookie => "unable to set cookie",
+            ErrorKind::UnableToCaptureScreen => "unable to capture screen",
+            ErrorKind::UnexpectedAlertOpen => "unexpected alert open",
+            ErrorKind::UnknowCommand => "unknown command",
+            ErrorKind::UnknowError => "unknown error",
+            ErrorKind::UnknowMethod => "unknown method",
+            ErrorKind::UnsupportedOperation => "unsupported operation",
+            ErrorKind::Custom(error) => error
+        };
+        write!(f, "{}", description)
+    }
 }
\ No newline at end of file
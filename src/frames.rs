@@ -0,0 +1,84 @@
+//! Frames give access to the content of iframes
+
+use json::*;
+use std::ops::{Deref, DerefMut};
+use crate::tab::Tab;
+use crate::elements::ElementRef;
+
+/// The frame to switch to with [Tab::switch_to_frame()](../tab/struct.Tab.html#method.switch_to_frame).
+#[derive(Debug)]
+#[derive(Clone)]
+pub enum FrameId {
+    /// The top-level document of the tab.
+    Top,
+    /// The n-th frame of the current document (`window.frames[n]`).
+    Index(u16),
+    /// An `iframe` or `frame` element, like `FrameId::Element(element.into())`.
+    Element(ElementRef)
+}
+
+impl FrameId {
+    pub fn to_json(&self) -> JsonValue {
+        match self {
+            FrameId::Top => JsonValue::Null,
+            FrameId::Index(index) => (*index).into(),
+            FrameId::Element(element) => element.as_json_object()
+        }
+    }
+}
+
+/// A tab switched to a frame, returned by [Tab::frame()](../tab/struct.Tab.html#method.frame).
+/// It can be used like the tab itself, but searches and scripts run inside the frame.
+/// The tab switches back to the parent frame when this guard is dropped.
+///
+/// If another tab is selected in the meantime (for example by another thread), the frame is entered again before the next command of the guard.
+///
+/// # Example
+///
+/// ```rust
+/// # use lw_webdriver::{session::Session, enums::{Browser, Selector}, frames::FrameId};
+/// let mut session = Session::new(Browser::Firefox, false).unwrap();
+/// session.tabs[0].execute_script("document.body.innerHTML = '<iframe srcdoc=\"<p>Hello</p>\"></iframe>';", vec![]).unwrap();
+/// # std::thread::sleep(std::time::Duration::from_millis(200));
+///
+/// {
+///     let mut frame = session.tabs[0].frame(FrameId::Index(0)).unwrap();
+///     let paragraph = frame.find(Selector::Css, "p").unwrap().unwrap();
+///     assert_eq!(paragraph.get_text().unwrap(), "Hello");
+/// }
+///
+/// // back in the top-level document
+/// assert!(session.tabs[0].find(Selector::Css, "p").unwrap().is_none());
+/// ```
+pub struct Frame<'a> {
+    tab: &'a mut Tab
+}
+
+impl<'a> Frame<'a> {
+    pub(crate) fn new(tab: &'a mut Tab) -> Self {
+        Frame {
+            tab
+        }
+    }
+}
+
+impl<'a> Deref for Frame<'a> {
+    type Target = Tab;
+
+    fn deref(&self) -> &Tab {
+        self.tab
+    }
+}
+
+impl<'a> DerefMut for Frame<'a> {
+    fn deref_mut(&mut self) -> &mut Tab {
+        self.tab
+    }
+}
+
+impl<'a> Drop for Frame<'a> {
+    #[allow(unused_must_use)]
+    fn drop(&mut self) {
+        self.tab.client.leave_frame(&self.tab.session_id, &self.tab.id);
+    }
+}
@@ -1,576 +1,1084 @@
 use crate::timeouts::Timeouts;
-use crate::error::WebdriverError;
+use crate::error::{WebdriverError, ErrorKind};
+use crate::service::DriverOutput;
 use crate::enums::Selector;
+use crate::cookies::Cookie;
+use crate::base64;
+use crate::print::PrintOptions;
+use crate::actions::Actions;
+use crate::frames::FrameId;
+use crate::window::WindowRect;
+use crate::transport::{Transport, MinreqTransport, Request, Response, Method};
+use std::error::Error;
+use std::collections::HashMap;
+use std::sync::Mutex;
 use json::{JsonValue, object};
-use log::{debug, info, warn, error};
-
-/// used by requests sending data
-fn post(url: &str, body: &str) -> Result<JsonValue, WebdriverError> {
-    let res = minreq::post(url)
-        .with_body(body.to_string())
-        .send();
-
-    if let Ok(res) = res {
-        if let Ok(text) = res.as_str() {
-            if let Ok(json) = json::parse(text) {
-                if !json["value"]["error"].is_string() {
-                    Ok(json)
-                } else {
-                    error!("{:?}, response: {}", WebdriverError::from(json["value"]["error"].to_string()), json);
-                    Err(WebdriverError::from(json["value"]["error"].to_string()))
-                }
-            } else {
-                error!("WebdriverError::InvalidResponse (not json), text: {}, error: {:?}", text, json::parse(text));
-                Err(WebdriverError::InvalidResponse)
+use log::{debug, warn, error};
+
+/// Everything needed to send commands to a webdriver.
+/// It is shared by a session and all its tabs and elements.
+pub(crate) struct Client {
+    pub(crate) url: String,
+    pub(crate) transport: Box<dyn Transport>,
+    pub(crate) driver_output: Option<DriverOutput>,
+    /// Held while a tab is selected and used.
+    /// Contains the frames entered by the [Frame](../frames/struct.Frame.html) guards of each tab, to enter them again when the tab is selected.
+    selection: Mutex<HashMap<String, Vec<FrameId>>>
+}
+
+impl Client {
+    pub(crate) fn new(url: &str) -> Self {
+        Client {
+            url: url.trim_end_matches('/').to_string(),
+            transport: Box::new(MinreqTransport),
+            driver_output: None,
+            selection: Mutex::new(HashMap::new())
+        }
+    }
+
+    /// Send a command to the webdriver and read the response
+    pub(crate) fn run<T>(&self, command: Command<T>) -> Result<T, WebdriverError> {
+        let response = self.transport.send(&command.request(&self.url));
+        command.read_response(response, self.driver_output.as_ref())
+    }
+
+    /// Select a tab (if it is not selected yet)
+    pub(crate) fn select(&self, session_id: &str, tab_id: &str) -> Result<(), WebdriverError> {
+        let frames = self.selection.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.select_unlocked(session_id, tab_id, &frames)
+    }
+
+    /// Select a tab and send a command to it.
+    /// Other threads wait, so that they can't select another tab in between.
+    pub(crate) fn run_in_tab<T>(&self, session_id: &str, tab_id: &str, command: Command<T>) -> Result<T, WebdriverError> {
+        let frames = self.selection.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.select_unlocked(session_id, tab_id, &frames)?;
+        self.run(command)
+    }
+
+    /// Select a tab and switch to a frame, which is entered again each time the tab is selected until [leave_frame()](#method.leave_frame) is called
+    pub(crate) fn enter_frame(&self, session_id: &str, tab_id: &str, frame: &FrameId) -> Result<(), WebdriverError> {
+        let mut frames = self.selection.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.select_unlocked(session_id, tab_id, &frames)?;
+        self.run(switch_to_frame(session_id, frame))?;
+        frames.entry(tab_id.to_string()).or_default().push(frame.clone());
+        Ok(())
+    }
+
+    /// Select a tab and switch to the parent of the last frame entered with [enter_frame()](#method.enter_frame)
+    pub(crate) fn leave_frame(&self, session_id: &str, tab_id: &str) -> Result<(), WebdriverError> {
+        let mut frames = self.selection.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let result = self.select_unlocked(session_id, tab_id, &frames).and_then(|()| self.run(switch_to_parent_frame(session_id)));
+        if let Some(tab_frames) = frames.get_mut(tab_id) {
+            tab_frames.pop();
+            if tab_frames.is_empty() {
+                frames.remove(tab_id);
             }
-        } else {
-            error!("WebdriverError::InvalidResponse (not utf8), error: {:?}", res.as_str());
-            Err(WebdriverError::InvalidResponse)
         }
-    } else {
-        error!("WebdriverError::FailedRequest, error: {:?}", res);
-        Err(WebdriverError::FailedRequest)
+        result
+    }
+
+    fn select_unlocked(&self, session_id: &str, tab_id: &str, frames: &HashMap<String, Vec<FrameId>>) -> Result<(), WebdriverError> {
+        // check if it is needed to select the tab
+        if let Ok(id) = self.run(get_selected_tab(session_id)) {
+            if id == tab_id {
+                return Ok(());
+            }
+        }
+
+        self.run(select_tab(session_id, tab_id))?;
+        // selecting a tab resets its frame to the top-level document
+        for frame in frames.get(tab_id).into_iter().flatten() {
+            self.run(switch_to_frame(session_id, frame))?;
+        }
+        Ok(())
     }
 }
 
-/// use by requests getting data
-fn get(url: &str) -> Result<JsonValue, WebdriverError> {
-    let res = minreq::get(url)
-        .send();
+/// A webdriver command: the request to send and how to read the response.
+/// Commands are defined once in this module and run by the blocking [Client](struct.Client.html) and by the async client.
+pub(crate) struct Command<T> {
+    pub(crate) method: Method,
+    pub(crate) path: String,
+    pub(crate) body: Option<String>,
+    parse: Box<dyn FnOnce(JsonValue) -> Result<T, WebdriverError> + Send>
+}
 
-    if let Ok(res) = res {
-        if let Ok(text) = res.as_str() {
-            if let Ok(json) = json::parse(text) {
-                if !json["value"]["error"].is_string() {
-                    Ok(json)
-                } else {
-                    error!("{:?}, response: {}", WebdriverError::from(json["value"]["error"].to_string()), json);
-                    Err(WebdriverError::from(json["value"]["error"].to_string()))
-                }
-            } else {
-                error!("WebdriverError::InvalidResponse (not json), text: {}, error: {:?}", text, json::parse(text));
-                Err(WebdriverError::InvalidResponse)
-            }
-        } else {
-            error!("WebdriverError::InvalidResponse (not utf8), error: {:?}", res.as_str());
-            Err(WebdriverError::InvalidResponse)
+impl<T> Command<T> {
+    fn new<F>(method: Method, path: &str, body: Option<&str>, parse: F) -> Self where F: FnOnce(JsonValue) -> Result<T, WebdriverError> + Send + 'static {
+        Command {
+            method,
+            path: path.to_string(),
+            body: body.map(|b| b.to_string()),
+            parse: Box::new(parse)
         }
-    } else {
-        error!("WebdriverError::FailedRequest, error: {:?}", res);
-        Err(WebdriverError::FailedRequest)
+    }
+
+    /// used by requests sending data
+    fn post<F>(path: &str, body: &str, parse: F) -> Self where F: FnOnce(JsonValue) -> Result<T, WebdriverError> + Send + 'static {
+        Command::new(Method::Post, path, Some(body), parse)
+    }
+
+    /// use by requests getting data
+    fn get<F>(path: &str, parse: F) -> Self where F: FnOnce(JsonValue) -> Result<T, WebdriverError> + Send + 'static {
+        Command::new(Method::Get, path, None, parse)
+    }
+
+    /// use by requests using delete http requests
+    fn delete<F>(path: &str, parse: F) -> Self where F: FnOnce(JsonValue) -> Result<T, WebdriverError> + Send + 'static {
+        Command::new(Method::Delete, path, None, parse)
+    }
+
+    /// The request to send to a webdriver listening at this url
+    pub(crate) fn request(&self, url: &str) -> Request {
+        Request {
+            method: self.method,
+            url: format!("{}{}", url, self.path),
+            body: self.body.clone()
+        }
+    }
+
+    /// Read the response of the webdriver (or the error of the transport)
+    pub(crate) fn read_response(self, response: Result<Response, Box<dyn Error + Send + Sync>>, driver_output: Option<&DriverOutput>) -> Result<T, WebdriverError> {
+        let json = parse_response(response)
+            .map_err(|e| attach_driver_output(e.with_command(self.method.to_string(), &self.path), driver_output))?;
+        (self.parse)(json)
     }
 }
 
-/// use by requests using delete http requests
-fn delete(url: &str) -> Result<JsonValue, WebdriverError> {
-    let res = minreq::delete(url)
-        .send();
+/// Attach the output of the webdriver process to errors that may be caused by the webdriver itself
+fn attach_driver_output(error: WebdriverError, driver_output: Option<&DriverOutput>) -> WebdriverError {
+    match (driver_output, &error.kind) {
+        (Some(output), ErrorKind::SessionNotCreated) | (Some(output), ErrorKind::FailedRequest) => {
+            error.with_driver_output(output.get_text())
+        },
+        _ => error
+    }
+}
 
-    if let Ok(res) = res {
-        if let Ok(text) = res.as_str() {
-            if let Ok(json) = json::parse(text) {
-                if !json["value"]["error"].is_string() {
-                    Ok(json)
-                } else {
-                    error!("{:?}, response: {}", WebdriverError::from(json["value"]["error"].to_string()), json);
-                    Err(WebdriverError::from(json["value"]["error"].to_string()))
-                }
-            } else {
-                error!("WebdriverError::InvalidResponse (not json), text: {}, error: {:?}", text, json::parse(text));
-                Err(WebdriverError::InvalidResponse)
-            }
-        } else {
-            error!("WebdriverError::InvalidResponse (not utf8), error: {:?}", res.as_str());
-            Err(WebdriverError::InvalidResponse)
+/// percent-encode a value used in an url path
+fn encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte))
+        }
+    }
+    encoded
+}
+
+/// read the response of the webdriver
+fn parse_response(res: Result<Response, Box<dyn Error + Send + Sync>>) -> Result<JsonValue, WebdriverError> {
+    let res = match res {
+        Ok(res) => res,
+        Err(e) => {
+            error!("WebdriverError::FailedRequest, error: {:?}", e);
+            return Err(WebdriverError::from(ErrorKind::FailedRequest).with_boxed_source(e));
         }
+    };
+    let status = res.status;
+
+    let text = match std::str::from_utf8(&res.body) {
+        Ok(text) => text,
+        Err(e) => {
+            error!("WebdriverError::InvalidResponse (not utf8), error: {:?}", e);
+            return Err(WebdriverError::from(ErrorKind::InvalidResponse).with_status(status).with_source(e));
+        }
+    };
+
+    let json = match json::parse(text) {
+        Ok(json) => json,
+        Err(e) => {
+            error!("WebdriverError::InvalidResponse (not json), text: {}, error: {:?}", text, e);
+            return Err(WebdriverError::from(ErrorKind::InvalidResponse).with_status(status).with_source(e));
+        }
+    };
+
+    if json["value"]["error"].is_string() {
+        let error = WebdriverError::from_response(&json["value"]).with_status(status);
+        error!("{}, response: {}", error, json);
+        Err(error)
     } else {
-        error!("WebdriverError::FailedRequest, error: {:?}", res);
-        Err(WebdriverError::FailedRequest)
+        Ok(json)
     }
 }
 
 /// -> take capabilities (options)
 /// create a session
 /// -> return created session id
-pub(crate) fn new_session(capabilities: &str) -> Result<String, WebdriverError> {
+pub(crate) fn new_session(capabilities: &str) -> Command<String> {
     debug!("session creation request with capabilities {}", capabilities);
 
-    let json = post("http://localhost:4444/session", capabilities)?;
+    Command::post("/session", capabilities, |json| {
+        if json["value"]["sessionId"].is_string() {
+            let session_id = json["value"]["sessionId"].to_string();
+            debug!("session created (id: {})", session_id);
+            Ok(session_id)
+        } else {
+            error!("response to session creation request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
 
-    if json["value"]["sessionId"].is_string() {
-        let session_id = json["value"]["sessionId"].to_string();
-        debug!("session created (id: {})", session_id);
-        Ok(session_id)
-    } else {
-        error!("response to session creation request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+/// -> take session id
+/// end the session and close every tab
+pub(crate) fn delete_session(session_id: &str) -> Command<()> {
+    debug!("deleting session with id {}", session_id);
+
+    Command::delete(&format!("/session/{}", session_id), |json| {
+        if json["value"].is_null() {
+            debug!("session deleted");
+            Ok(())
+        } else {
+            error!("response to session deletion request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id
 /// create a tab on this session
 /// -> return created tab id
-pub(crate) fn new_tab(session_id: &str) -> Result<String, WebdriverError> {
+pub(crate) fn new_tab(session_id: &str) -> Command<String> {
     debug!("tab creation request on session with id {}", session_id);
 
-    let json = post(&format!("http://localhost:4444/session/{}/window/new", session_id), "{}")?;
-
-    if json["value"]["handle"].is_string() {
-        let session_id = json["value"]["handle"].to_string();
-        debug!("tab created (id: {})", session_id);
-        Ok(session_id)
-    } else {
-        error!("response to session creation request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    Command::post(&format!("/session/{}/window/new", session_id), "{}", |json| {
+        if json["value"]["handle"].is_string() {
+            let session_id = json["value"]["handle"].to_string();
+            debug!("tab created (id: {})", session_id);
+            Ok(session_id)
+        } else {
+            error!("response to session creation request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id
 /// -> return every open tab ids
-pub(crate) fn get_open_tabs(session_id: &str) -> Result<Vec<String>, WebdriverError> {
+pub(crate) fn get_open_tabs(session_id: &str) -> Command<Vec<String>> {
     debug!("getting ids of open tabs on session with id {}", session_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/window/handles", session_id))?;
-
-    if !json["value"].is_null() {
-        let mut tabs: Vec<String> = Vec::new();
-        let mut i = 0;
-        while !json["value"][i].is_null() {
-            tabs.push(json["value"][i].to_string());
-            i += 1;
+    Command::get(&format!("/session/{}/window/handles", session_id), |json| {
+        if !json["value"].is_null() {
+            let mut tabs: Vec<String> = Vec::new();
+            let mut i = 0;
+            while !json["value"][i].is_null() {
+                tabs.push(json["value"][i].to_string());
+                i += 1;
+            }
+            debug!("ids of open tabs: {:?}", tabs);
+            Ok(tabs)
+        } else {
+            error!("response to open tab ids request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
         }
-        debug!("ids of open tabs: {:?}", session_id);
-        Ok(tabs)
-    } else {
-        error!("response to open tab ids request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    })
 }
 
 /// -> take session id
 /// -> return selected tab id
-pub(crate) fn get_selected_tab(session_id: &str) -> Result<String, WebdriverError> {
+pub(crate) fn get_selected_tab(session_id: &str) -> Command<String> {
     debug!("getting id of the selected tab on session with id {}", session_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/window", session_id))?;
-
-    if json["value"].is_string() {
-        let id = json["value"].to_string();
-        debug!("the selected tab id is {}", id);
-        Ok(id)
-    } else {
-        error!("response to selected tab id request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    Command::get(&format!("/session/{}/window", session_id), |json| {
+        if json["value"].is_string() {
+            let id = json["value"].to_string();
+            debug!("the selected tab id is {}", id);
+            Ok(id)
+        } else {
+            error!("response to selected tab id request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id
 /// -> return timeouts
-pub(crate) fn get_timeouts(session_id: &str) -> Result<Timeouts, WebdriverError> {
+pub(crate) fn get_timeouts(session_id: &str) -> Command<Timeouts> {
     debug!("getting timeouts on session with id {}", session_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/timeouts", session_id))?;
-
-    if json["value"]["pageLoad"].is_number() && json["value"]["implicit"].is_number() {
-        let timeouts = Timeouts{
-            script: json["value"]["script"].as_usize(),
-            page_load: json["value"]["pageLoad"].as_usize().unwrap(),
-            implicit: json["value"]["implicit"].as_usize().unwrap(),
-        };
-        debug!("timeouts are {:?}", timeouts);
-        Ok(timeouts)
-    } else {
-        error!("response to timeouts request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    Command::get(&format!("/session/{}/timeouts", session_id), |json| {
+        if json["value"]["pageLoad"].is_number() && json["value"]["implicit"].is_number() {
+            let timeouts = Timeouts{
+                script: json["value"]["script"].as_usize(),
+                page_load: json["value"]["pageLoad"].as_usize().unwrap(),
+                implicit: json["value"]["implicit"].as_usize().unwrap(),
+            };
+            debug!("timeouts are {:?}", timeouts);
+            Ok(timeouts)
+        } else {
+            error!("response to timeouts request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id and timeouts
 /// set timeouts
-pub(crate) fn set_timeouts(session_id: &str, timeouts: Timeouts) -> Result<(), WebdriverError> {
+pub(crate) fn set_timeouts(session_id: &str, timeouts: Timeouts) -> Command<()> {
     debug!("setting timeouts to {:?} on session with id {}", timeouts, session_id);
 
-    let json = post(&format!("http://localhost:4444/session/{}/timeouts", session_id), &timeouts.to_json().to_string())?;
-
-    if json["value"].is_null() {
-        debug!("setting timeouts succeed");
-        Ok(())
-    } else {
-        error!("response to timeouts change request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    Command::post(&format!("/session/{}/timeouts", session_id), &timeouts.to_json().to_string(), |json| {
+        if json["value"].is_null() {
+            debug!("setting timeouts succeed");
+            Ok(())
+        } else {
+            error!("response to timeouts change request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id and tab id
 /// select tab
-pub(crate) fn select_tab(session_id: &str, tab_id: &str) -> Result<(), WebdriverError> {
+pub(crate) fn select_tab(session_id: &str, tab_id: &str) -> Command<()> {
     debug!("selecting tab with id {} on session with id {}", tab_id, session_id);
 
-    let json = post(&format!("http://localhost:4444/session/{}/window", session_id), &object! {
+    Command::post(&format!("/session/{}/window", session_id), &object! {
         "handle" => tab_id,
-    }.to_string())?;
-
-    if json["value"].is_null() {
-        debug!("selecting tab succeed");
-        Ok(())
-    } else {
-        error!("response to tab selection request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    }.to_string(), |json| {
+        if json["value"].is_null() {
+            debug!("selecting tab succeed");
+            Ok(())
+        } else {
+            error!("response to tab selection request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id and a valid url
 /// load a website in the selected tab
-pub(crate) fn navigate(session_id: &str, url: &str) -> Result<(), WebdriverError> {
+pub(crate) fn navigate(session_id: &str, url: &str) -> Command<()> {
     debug!("navigating to {} on session with id {}", url, session_id);
 
-    let json = post(&format!("http://localhost:4444/session/{}/url", session_id), &object! {
+    Command::post(&format!("/session/{}/url", session_id), &object! {
         "url" => url,
-    }.to_string())?;
-
-    if json["value"].is_null() {
-        debug!("navigation succeed");
-        Ok(())
-    } else {
-        error!("response to navigation request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    }.to_string(), |json| {
+        if json["value"].is_null() {
+            debug!("navigation succeed");
+            Ok(())
+        } else {
+            error!("response to navigation request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id
 /// close active tab
-pub(crate) fn close_active_tab(session_id: &str) -> Result<(), WebdriverError> {
+pub(crate) fn close_active_tab(session_id: &str) -> Command<()> {
     debug!("closing active tab on session with id {}", session_id);
 
-    let json = delete(&format!("http://localhost:4444/session/{}/window", session_id))?;
-
-    if json["value"].is_array() || json["value"].is_null() {
-        debug!("tab closed successfully");
-        Ok(())
-    } else {
-        error!("response to close request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    Command::delete(&format!("/session/{}/window", session_id), |json| {
+        if json["value"].is_array() || json["value"].is_null() {
+            debug!("tab closed successfully");
+            Ok(())
+        } else {
+            error!("response to close request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id, a selector and a value
 /// search for elements
 /// -> return id of the first element found
-pub(crate) fn find_element(session_id: &str, selector: Selector, value: &str) -> Result<String, WebdriverError> {
+pub(crate) fn find_element(session_id: &str, selector: Selector, value: &str) -> Command<String> {
     debug!("selecting element by {} with value {} on session with id {}", selector.to_string(), value, session_id);
 
-    let json = post(&format!("http://localhost:4444/session/{}/element", session_id), &object! {
+    Command::post(&format!("/session/{}/element", session_id), &object! {
         "using" => selector.to_string(),
         "value" => value
-    }.to_string())?;
+    }.to_string(), |json| {
+        if !json["value"]["element-6066-11e4-a52e-4f735466cecf"].is_null() {
+            debug!("element found");
+            Ok(json["value"]["element-6066-11e4-a52e-4f735466cecf"].to_string())
+        } else {
+            error!("response to element search request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
 
-    if !json["value"]["element-6066-11e4-a52e-4f735466cecf"].is_null() {
-        debug!("element found");
-        Ok(json["value"]["element-6066-11e4-a52e-4f735466cecf"].to_string())
-    } else {
-        error!("response to element search request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+/// -> take session id, a selector and a value
+/// search for elements
+/// -> return ids of every element found
+pub(crate) fn find_elements(session_id: &str, selector: Selector, value: &str) -> Command<Vec<String>> {
+    debug!("selecting elements by {} with value {} on session with id {}", selector.to_string(), value, session_id);
+
+    Command::post(&format!("/session/{}/elements", session_id), &object! {
+        "using" => selector.to_string(),
+        "value" => value
+    }.to_string(), |json| {
+        if json["value"].is_array() {
+            let mut elements = Vec::new();
+            for element in json["value"].members() {
+                if element["element-6066-11e4-a52e-4f735466cecf"].is_string() {
+                    elements.push(element["element-6066-11e4-a52e-4f735466cecf"].to_string());
+                } else {
+                    error!("response to elements search request was not understood: {}", json);
+                    return Err(ErrorKind::InvalidResponse.into());
+                }
+            }
+            debug!("{} elements found", elements.len());
+            Ok(elements)
+        } else {
+            error!("response to elements search request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id, element id, a selector and a value
+/// search for elements inside the element
+/// -> return id of the first element found
+pub(crate) fn find_element_from_element(session_id: &str, element_id: &str, selector: Selector, value: &str) -> Command<String> {
+    debug!("selecting element by {} with value {} in element with id {} on session with id {}", selector.to_string(), value, element_id, session_id);
+
+    Command::post(&format!("/session/{}/element/{}/element", session_id, element_id), &object! {
+        "using" => selector.to_string(),
+        "value" => value
+    }.to_string(), |json| {
+        if json["value"]["element-6066-11e4-a52e-4f735466cecf"].is_string() {
+            debug!("element found");
+            Ok(json["value"]["element-6066-11e4-a52e-4f735466cecf"].to_string())
+        } else {
+            error!("response to element search request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id, element id, a selector and a value
+/// search for elements inside the element
+/// -> return ids of every element found
+pub(crate) fn find_elements_from_element(session_id: &str, element_id: &str, selector: Selector, value: &str) -> Command<Vec<String>> {
+    debug!("selecting elements by {} with value {} in element with id {} on session with id {}", selector.to_string(), value, element_id, session_id);
+
+    Command::post(&format!("/session/{}/element/{}/elements", session_id, element_id), &object! {
+        "using" => selector.to_string(),
+        "value" => value
+    }.to_string(), |json| {
+        if json["value"].is_array() {
+            let mut elements = Vec::new();
+            for element in json["value"].members() {
+                if element["element-6066-11e4-a52e-4f735466cecf"].is_string() {
+                    elements.push(element["element-6066-11e4-a52e-4f735466cecf"].to_string());
+                } else {
+                    error!("response to elements search request was not understood: {}", json);
+                    return Err(ErrorKind::InvalidResponse.into());
+                }
+            }
+            debug!("{} elements found", elements.len());
+            Ok(elements)
+        } else {
+            error!("response to elements search request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id
 /// -> return url of the active tab
-pub(crate) fn get_active_tab_url(session_id: &str) -> Result<String, WebdriverError> {
+pub(crate) fn get_active_tab_url(session_id: &str) -> Command<String> {
     debug!("getting url of active tab on session with id {}", session_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/url", session_id))?;
-
-    if json["value"].is_string() {
-        let url = json["value"].to_string();
-        debug!("active tab url is {}", url);
-        Ok(url)
-    } else {
-        error!("response to url request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    Command::get(&format!("/session/{}/url", session_id), |json| {
+        if json["value"].is_string() {
+            let url = json["value"].to_string();
+            debug!("active tab url is {}", url);
+            Ok(url)
+        } else {
+            error!("response to url request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id
 /// -> return title of the active tab
-pub(crate) fn get_active_tab_title(session_id: &str) -> Result<String, WebdriverError> {
+pub(crate) fn get_active_tab_title(session_id: &str) -> Command<String> {
     debug!("getting title of active tab on session with id {}", session_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/title", session_id))?;
-
-    if json["value"].is_string() {
-        let url = json["value"].to_string();
-        debug!("active tab title is {}", url);
-        Ok(url)
-    } else {
-        error!("response to title request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    Command::get(&format!("/session/{}/title", session_id), |json| {
+        if json["value"].is_string() {
+            let url = json["value"].to_string();
+            debug!("active tab title is {}", url);
+            Ok(url)
+        } else {
+            error!("response to title request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id
 /// navigate backward on the selected tab
-pub(crate) fn back(session_id: &str) -> Result<(), WebdriverError> {
+pub(crate) fn back(session_id: &str) -> Command<()> {
     debug!("navigating backward on active tab on session with id {}", session_id);
 
-    let json = post(&format!("http://localhost:4444/session/{}/back", session_id), "{}")?;
-
-    if json["value"].is_null() {
-        debug!("successfully navigated backward");
-        Ok(())
-    } else {
-        error!("response to back request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    Command::post(&format!("/session/{}/back", session_id), "{}", |json| {
+        if json["value"].is_null() {
+            debug!("successfully navigated backward");
+            Ok(())
+        } else {
+            error!("response to back request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id
 /// navigate forward on the selected tab
-pub(crate) fn forward(session_id: &str) -> Result<(), WebdriverError> {
+pub(crate) fn forward(session_id: &str) -> Command<()> {
     debug!("navigating forward on active tab on session with id {}", session_id);
 
-    let json = post(&format!("http://localhost:4444/session/{}/forward", session_id), "{}")?;
-
-    if json["value"].is_null() {
-        debug!("successfully navigated forward");
-        Ok(())
-    } else {
-        error!("response to forward request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    Command::post(&format!("/session/{}/forward", session_id), "{}", |json| {
+        if json["value"].is_null() {
+            debug!("successfully navigated forward");
+            Ok(())
+        } else {
+            error!("response to forward request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id
 /// refresh the selected tab
-pub(crate) fn refresh(session_id: &str) -> Result<(), WebdriverError> {
+pub(crate) fn refresh(session_id: &str) -> Command<()> {
     debug!("refreshing the active tab on session with id {}", session_id);
 
-    let json = post(&format!("http://localhost:4444/session/{}/refresh", session_id), "{}")?;
-
-    if json["value"].is_null() {
-        debug!("tab successfully refreshed");
-        Ok(())
-    } else {
-        error!("response to refresh request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    Command::post(&format!("/session/{}/refresh", session_id), "{}", |json| {
+        if json["value"].is_null() {
+            debug!("tab successfully refreshed");
+            Ok(())
+        } else {
+            error!("response to refresh request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
 /// -> take session id, script and args
 /// execute the script on the active tab
-pub(crate) fn execute_script_sync(session_id: &str, script: &str, args: Vec<JsonValue>) -> Result<(), WebdriverError> {
+/// -> return the value returned by the script
+pub(crate) fn execute_script_sync(session_id: &str, script: &str, args: Vec<JsonValue>) -> Command<JsonValue> {
     debug!("executing script on selected tab on session with id {}", session_id);
 
-    let json = post(&format!("http://localhost:4444/session/{}/execute/sync", session_id), &object!{
+    Command::post(&format!("/session/{}/execute/sync", session_id), &object!{
         "script" => script,
         "args" => args
-    }.to_string())?;
+    }.to_string(), |mut json| {
+        if json.has_key("value") {
+            debug!("script successfully executed");
+            Ok(json["value"].take())
+        } else {
+            error!("response to script execution request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
 
-    if json["value"].is_null() {
-        debug!("script successfully executed");
-        Ok(())
-    } else {
-        error!("response to refresh request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+/// -> take session id, script and args
+/// execute the script on the active tab and wait for it to call its callback
+/// -> return the value passed to the callback
+pub(crate) fn execute_script_async(session_id: &str, script: &str, args: Vec<JsonValue>) -> Command<JsonValue> {
+    debug!("executing async script on selected tab on session with id {}", session_id);
+
+    Command::post(&format!("/session/{}/execute/async", session_id), &object!{
+        "script" => script,
+        "args" => args
+    }.to_string(), |mut json| {
+        if json.has_key("value") {
+            debug!("async script successfully executed");
+            Ok(json["value"].take())
+        } else {
+            error!("response to async script execution request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
-pub(crate) fn click_on_element(session_id: &str, element_id: &str) -> Result<(), WebdriverError> {
+pub(crate) fn click_on_element(session_id: &str, element_id: &str) -> Command<()> {
     debug!("clicking on element with id {} on session with id {}", session_id, element_id);
     warn!("click_on_element function may fail silently in firefox");
 
-    let json = post(&format!("http://localhost:4444/session/{}/element/{}/click", session_id, element_id), "{}")?;
-
-    if json["value"].is_null() {
-        debug!("clicked successfully");
-        Ok(())
-    } else {
-        error!("response to click request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    Command::post(&format!("/session/{}/element/{}/click", session_id, element_id), "{}", |json| {
+        if json["value"].is_null() {
+            debug!("clicked successfully");
+            Ok(())
+        } else {
+            error!("response to click request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
-pub(crate) fn get_element_text(session_id: &str, element_id: &str) -> Result<String, WebdriverError> {
+pub(crate) fn get_element_text(session_id: &str, element_id: &str) -> Command<String> {
     debug!("getting text of element with id {} on session with id {}", session_id, element_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/element/{}/text", session_id, element_id))?;
-
-    if json["value"].is_string() {
-        let text = json["value"].to_string();
-        debug!("text of element with id {} is {}", element_id, text);
-        Ok(text)
-    } else {
-        error!("response to text request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    let element_id = element_id.to_string();
+    Command::get(&format!("/session/{}/element/{}/text", session_id, element_id), move |json| {
+        if json["value"].is_string() {
+            let text = json["value"].to_string();
+            debug!("text of element with id {} is {}", element_id, text);
+            Ok(text)
+        } else {
+            error!("response to text request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
-pub(crate) fn send_text_to_element(session_id: &str, element_id: &str, text: &str) -> Result<(), WebdriverError> {
+pub(crate) fn send_text_to_element(session_id: &str, element_id: &str, text: &str) -> Command<()> {
     debug!("sending text ({}) to element with id {} on session with id {}", text, session_id, element_id);
 
-    let json = post(&format!("http://localhost:4444/session/{}/element/{}/value", session_id, element_id), &object!{
+    Command::post(&format!("/session/{}/element/{}/value", session_id, element_id), &object!{
         "text" => text,
-    }.to_string())?;
-
-    if json["value"].is_null() {
-        debug!("success");
-        Ok(())
-    } else {
-        error!("response to send text request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    }.to_string(), |json| {
+        if json["value"].is_null() {
+            debug!("success");
+            Ok(())
+        } else {
+            error!("response to send text request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
-pub(crate) fn get_element_attribute(session_id: &str, element_id: &str, attribute_name: &str) -> Result<String, WebdriverError> {
+pub(crate) fn get_element_attribute(session_id: &str, element_id: &str, attribute_name: &str) -> Command<String> {
     debug!("getting attribute {} of element with id {} on session with id {}", attribute_name, session_id, element_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/element/{}/attribute/{}", session_id, element_id, attribute_name))?;
-
-    if json["value"].is_string() {
-        let value = json["value"].to_string();
-        debug!("attribute {} is {}", attribute_name, value);
-        Ok(value)
-    } else {
-        error!("response to get element attribute request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    let attribute_name = attribute_name.to_string();
+    Command::get(&format!("/session/{}/element/{}/attribute/{}", session_id, element_id, attribute_name), move |json| {
+        if json["value"].is_string() {
+            let value = json["value"].to_string();
+            debug!("attribute {} is {}", attribute_name, value);
+            Ok(value)
+        } else {
+            error!("response to get element attribute request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
-pub(crate) fn get_element_property(session_id: &str, element_id: &str, property_name: &str) -> Result<String, WebdriverError> {
+pub(crate) fn get_element_property(session_id: &str, element_id: &str, property_name: &str) -> Command<String> {
     debug!("getting property {} of element with id {} on session with id {}", property_name, session_id, element_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/element/{}/property/{}", session_id, element_id, property_name))?;
-
-    if !json["value"].is_null() {
-        let value = json["value"].to_string();
-        debug!("property {} is {}", property_name, value);
-        Ok(value)
-    } else {
-        error!("response to get element property request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    let property_name = property_name.to_string();
+    Command::get(&format!("/session/{}/element/{}/property/{}", session_id, element_id, property_name), move |json| {
+        if !json["value"].is_null() {
+            let value = json["value"].to_string();
+            debug!("property {} is {}", property_name, value);
+            Ok(value)
+        } else {
+            error!("response to get element property request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
-pub(crate) fn get_element_css_value(session_id: &str, element_id: &str, property_name: &str) -> Result<String, WebdriverError> {
+pub(crate) fn get_element_css_value(session_id: &str, element_id: &str, property_name: &str) -> Command<String> {
     debug!("getting css value of property {} of element with id {} on session with id {}", property_name, session_id, element_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/element/{}/css/{}", session_id, element_id, property_name))?;
-
-    if json["value"].is_string() {
-        let value = json["value"].to_string();
-        debug!("css value for {} is {}", property_name, value);
-        Ok(value)
-    } else {
-        error!("response to get element css value request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    let property_name = property_name.to_string();
+    Command::get(&format!("/session/{}/element/{}/css/{}", session_id, element_id, property_name), move |json| {
+        if json["value"].is_string() {
+            let value = json["value"].to_string();
+            debug!("css value for {} is {}", property_name, value);
+            Ok(value)
+        } else {
+            error!("response to get element css value request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
-pub(crate) fn get_element_tag_name(session_id: &str, element_id: &str) -> Result<String, WebdriverError> {
+pub(crate) fn get_element_tag_name(session_id: &str, element_id: &str) -> Command<String> {
     debug!("getting tag name of element with id {} on session with id {}", session_id, element_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/element/{}/name", session_id, element_id))?;
-
-    if json["value"].is_string() {
-        let value = json["value"].to_string();
-        debug!("tag name is {}", value);
-        Ok(value)
-    } else {
-        error!("response to get element tag name request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    Command::get(&format!("/session/{}/element/{}/name", session_id, element_id), |json| {
+        if json["value"].is_string() {
+            let value = json["value"].to_string();
+            debug!("tag name is {}", value);
+            Ok(value)
+        } else {
+            error!("response to get element tag name request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
-pub(crate) fn get_element_rect(session_id: &str, element_id: &str) -> Result<((usize, usize), (usize, usize)), WebdriverError> {
+pub(crate) fn get_element_rect(session_id: &str, element_id: &str) -> Command<((usize, usize), (usize, usize))> {
     debug!("getting rect of element with id {} on session with id {}", session_id, element_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/element/{}/rect", session_id, element_id))?;
-
-    if json["value"]["x"].is_number() && json["value"]["y"].is_number() && json["value"]["width"].is_number() && json["value"]["height"].is_number() {
-        let value = ((json["value"]["x"].as_usize().unwrap(), json["value"]["y"].as_usize().unwrap()), (json["value"]["width"].as_usize().unwrap(), json["value"]["height"].as_usize().unwrap()));
-        debug!("rect is {:?}", value);
-        Ok(value)
-    } else {
-        error!("response to get element rect request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+    Command::get(&format!("/session/{}/element/{}/rect", session_id, element_id), |json| {
+        if json["value"]["x"].is_number() && json["value"]["y"].is_number() && json["value"]["width"].is_number() && json["value"]["height"].is_number() {
+            let value = ((json["value"]["x"].as_usize().unwrap(), json["value"]["y"].as_usize().unwrap()), (json["value"]["width"].as_usize().unwrap(), json["value"]["height"].as_usize().unwrap()));
+            debug!("rect is {:?}", value);
+            Ok(value)
+        } else {
+            error!("response to get element rect request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
-pub(crate) fn is_element_enabled(session_id: &str, element_id: &str) -> Result<bool, WebdriverError> {
+pub(crate) fn is_element_enabled(session_id: &str, element_id: &str) -> Command<bool> {
     debug!("checking if element with id {} on session with id {} is enabled", element_id, session_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/element/{}/enabled", session_id, element_id))?;
+    Command::get(&format!("/session/{}/element/{}/enabled", session_id, element_id), |json| {
+        if json["value"].is_boolean() {
+            let value = json["value"].as_bool().unwrap();
+            Ok(value)
+        } else {
+            error!("response to is element enabled request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
 
-    if json["value"].is_boolean() {
-        let value = json["value"].as_bool().unwrap();
-        Ok(value)
-    } else {
-        error!("response to is element enabled request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+pub(crate) fn is_element_displayed(session_id: &str, element_id: &str) -> Command<bool> {
+    debug!("checking if element with id {} on session with id {} is displayed", element_id, session_id);
+
+    Command::get(&format!("/session/{}/element/{}/displayed", session_id, element_id), |json| {
+        if json["value"].is_boolean() {
+            let value = json["value"].as_bool().unwrap();
+            Ok(value)
+        } else {
+            error!("response to is element displayed request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
-pub(crate) fn get_all_cookies(session_id: &str) -> Result<Vec<(String, usize, bool, String, String, bool, String)>, WebdriverError> {
+pub(crate) fn get_all_cookies(session_id: &str) -> Command<Vec<Cookie>> {
     debug!("getting cookies on session with id {}", session_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/cookie", session_id))?;
-
-    if json["value"].is_array() {
-        let mut i = 0;
-        let mut cookies = Vec::new();
-        while json["value"][i].is_object() {
-            if let (domain, Some(expiry), Some(http_only), name, path, Some(secure), value) =
-            (json["value"][i]["domain"].to_string(), json["value"][i]["expiry"].as_usize(), json["value"][i]["httpOnly"].as_bool(), json["value"][i]["name"].to_string(), json["value"][i]["path"].to_string(), json["value"][i]["secure"].as_bool(), json["value"][i]["value"].to_string()) {
-                cookies.push((domain, expiry, http_only, name, path, secure, value))
-            } else {
-                warn!("a cookie was invalid; result: {:?}", (json["value"][i]["domain"].to_string(), json["value"][i]["expiry"].as_usize(), json["value"][i]["httpOnly"].as_bool(), json["value"][i]["name"].to_string(), json["value"][i]["path"].to_string(), json["value"][i]["secure"].as_bool(), json["value"][i]["value"].to_string()))
+    Command::get(&format!("/session/{}/cookie", session_id), |json| {
+        if json["value"].is_array() {
+            let mut cookies = Vec::new();
+            for cookie in json["value"].members() {
+                if let Some(cookie) = Cookie::from_json(cookie) {
+                    cookies.push(cookie)
+                } else {
+                    warn!("a cookie was invalid: {}", cookie)
+                }
             }
-            i += 1;
+            
+            debug!("cookies: {:?}", cookies);
+
+            Ok(cookies)
+        } else {
+            error!("response to cookies request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
         }
-        
-        debug!("cookies: {:?}", cookies);
+    })
+}
 
-        Ok(cookies)
-    } else {
-        error!("response to cookies request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+pub(crate) fn get_named_cookie(session_id: &str, name: &str) -> Command<Cookie> {
+    debug!("getting cookie {} on session with id {}", name, session_id);
+
+    Command::get(&format!("/session/{}/cookie/{}", session_id, encode(name)), |json| {
+        if let Some(cookie) = Cookie::from_json(&json["value"]) {
+            debug!("cookie: {:?}", cookie);
+            Ok(cookie)
+        } else {
+            error!("response to cookie request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
-pub(crate) fn set_cookie(session_id: &str, cookie: (String, usize, bool, String, String, bool, String)) -> Result<(), WebdriverError> {
-    debug!("setting cookie {} to {} on session with id {}", cookie.3, cookie.6, session_id);
+pub(crate) fn set_cookie(session_id: &str, cookie: &Cookie) -> Command<()> {
+    debug!("setting cookie {} to {} on session with id {}", cookie.name, cookie.value, session_id);
 
-    let json = post(&format!("http://localhost:4444/session/{}/cookie", session_id), &object!{
-        "cookie" => object!{
-            "domain" => cookie.0,
-            "expiry" => cookie.1,
-            "httpOnly" => cookie.2,
-            "name" => cookie.3,
-            "path" => cookie.4,
-            "secure" => cookie.5,
-            "value" => cookie.6
+    Command::post(&format!("/session/{}/cookie", session_id), &object!{
+        "cookie" => cookie.to_json()
+    }.to_string(), |json| {
+        if json["value"].is_null() {
+            debug!("success");
+            Ok(())
+        } else {
+            error!("response to add cookie request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
         }
-    }.to_string())?;
+    })
+}
 
-    if json["value"].is_null() {
-        debug!("success");
-        Ok(())
-    } else {
-        error!("response to add cookie request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
+pub(crate) fn delete_cookie(session_id: &str, name: &str) -> Command<()> {
+    debug!("deleting cookie {} on session with id {}", name, session_id);
+
+    Command::delete(&format!("/session/{}/cookie/{}", session_id, encode(name)), |json| {
+        if json["value"].is_null() {
+            debug!("success");
+            Ok(())
+        } else {
+            error!("response to delete cookie request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+pub(crate) fn delete_all_cookies(session_id: &str) -> Command<()> {
+    debug!("deleting all cookies on session with id {}", session_id);
+
+    Command::delete(&format!("/session/{}/cookie", session_id), |json| {
+        if json["value"].is_null() {
+            debug!("success");
+            Ok(())
+        } else {
+            error!("response to delete all cookies request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id
+/// -> return the PNG screenshot of the active tab
+pub(crate) fn take_screenshot(session_id: &str) -> Command<Vec<u8>> {
+    debug!("taking screenshot of active tab on session with id {}", session_id);
+
+    Command::get(&format!("/session/{}/screenshot", session_id), |json| {
+        if let Some(png) = json["value"].as_str().and_then(base64::decode) {
+            debug!("screenshot taken ({} bytes)", png.len());
+            Ok(png)
+        } else {
+            error!("response to screenshot request was not understood");
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id and element id
+/// -> return the PNG screenshot of the element
+pub(crate) fn take_element_screenshot(session_id: &str, element_id: &str) -> Command<Vec<u8>> {
+    debug!("taking screenshot of element with id {} on session with id {}", element_id, session_id);
+
+    Command::get(&format!("/session/{}/element/{}/screenshot", session_id, element_id), |json| {
+        if let Some(png) = json["value"].as_str().and_then(base64::decode) {
+            debug!("screenshot taken ({} bytes)", png.len());
+            Ok(png)
+        } else {
+            error!("response to element screenshot request was not understood");
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id and print options
+/// -> return the PDF document of the active tab
+pub(crate) fn print_page(session_id: &str, options: &PrintOptions) -> Command<Vec<u8>> {
+    debug!("printing active tab with options {:?} on session with id {}", options, session_id);
+
+    Command::post(&format!("/session/{}/print", session_id), &options.to_json().to_string(), |json| {
+        if let Some(pdf) = json["value"].as_str().and_then(base64::decode) {
+            debug!("page printed ({} bytes)", pdf.len());
+            Ok(pdf)
+        } else {
+            error!("response to print request was not understood");
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id and actions
+/// perform the actions on the active tab
+pub(crate) fn perform_actions(session_id: &str, actions: &Actions) -> Command<()> {
+    debug!("performing actions {} on session with id {}", actions.to_json(), session_id);
+
+    Command::post(&format!("/session/{}/actions", session_id), &actions.to_json().to_string(), |json| {
+        if json["value"].is_null() {
+            debug!("actions performed");
+            Ok(())
+        } else {
+            error!("response to actions request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id
+/// release every pressed key and button
+pub(crate) fn release_actions(session_id: &str) -> Command<()> {
+    debug!("releasing actions on session with id {}", session_id);
+
+    Command::delete(&format!("/session/{}/actions", session_id), |json| {
+        if json["value"].is_null() {
+            debug!("actions released");
+            Ok(())
+        } else {
+            error!("response to release actions request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id
+/// -> return the message of the opened dialog
+pub(crate) fn get_alert_text(session_id: &str) -> Command<String> {
+    debug!("getting alert text on session with id {}", session_id);
+
+    Command::get(&format!("/session/{}/alert/text", session_id), |json| {
+        if json["value"].is_string() || json["value"].is_null() {
+            let text = json["value"].as_str().unwrap_or("").to_string();
+            debug!("alert text is {}", text);
+            Ok(text)
+        } else {
+            error!("response to alert text request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id and alert command ("accept" or "dismiss")
+pub(crate) fn close_alert(session_id: &str, command: &'static str) -> Command<()> {
+    debug!("closing alert ({}) on session with id {}", command, session_id);
+
+    Command::post(&format!("/session/{}/alert/{}", session_id, command), "{}", move |json| {
+        if json["value"].is_null() {
+            debug!("alert closed");
+            Ok(())
+        } else {
+            error!("response to {} alert request was not understood: {}", command, json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
 }
 
-pub(crate) fn get_page_source(session_id: &str) -> Result<String, WebdriverError> {
+/// -> take session id and text
+/// type text in the opened prompt
+pub(crate) fn send_alert_text(session_id: &str, text: &str) -> Command<()> {
+    debug!("sending text ({}) to alert on session with id {}", text, session_id);
+
+    Command::post(&format!("/session/{}/alert/text", session_id), &object!{
+        "text" => text,
+    }.to_string(), |json| {
+        if json["value"].is_null() {
+            debug!("success");
+            Ok(())
+        } else {
+            error!("response to send alert text request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id and frame id
+/// switch the active tab to a frame
+pub(crate) fn switch_to_frame(session_id: &str, frame: &FrameId) -> Command<()> {
+    debug!("switching to frame {} on session with id {}", frame.to_json(), session_id);
+
+    Command::post(&format!("/session/{}/frame", session_id), &object!{
+        "id" => frame.to_json(),
+    }.to_string(), |json| {
+        if json["value"].is_null() {
+            debug!("frame switched");
+            Ok(())
+        } else {
+            error!("response to switch frame request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id
+/// switch the active tab to the parent of the current frame
+pub(crate) fn switch_to_parent_frame(session_id: &str) -> Command<()> {
+    debug!("switching to parent frame on session with id {}", session_id);
+
+    Command::post(&format!("/session/{}/frame/parent", session_id), "{}", |json| {
+        if json["value"].is_null() {
+            debug!("frame switched");
+            Ok(())
+        } else {
+            error!("response to switch to parent frame request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id
+/// -> return the position and the size of the window of the active tab
+pub(crate) fn get_window_rect(session_id: &str) -> Command<WindowRect> {
+    debug!("getting window rect on session with id {}", session_id);
+
+    Command::get(&format!("/session/{}/window/rect", session_id), |json| {
+        if let Some(rect) = WindowRect::from_json(&json["value"]) {
+            debug!("window rect is {:?}", rect);
+            Ok(rect)
+        } else {
+            error!("response to get window rect request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id and rect (fields can be null to keep them unchanged)
+/// -> return the new rect of the window
+pub(crate) fn set_window_rect(session_id: &str, rect: &JsonValue) -> Command<WindowRect> {
+    debug!("setting window rect to {} on session with id {}", rect, session_id);
+
+    Command::post(&format!("/session/{}/window/rect", session_id), &rect.to_string(), |json| {
+        if let Some(rect) = WindowRect::from_json(&json["value"]) {
+            debug!("window rect is {:?}", rect);
+            Ok(rect)
+        } else {
+            error!("response to set window rect request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+/// -> take session id and window command ("maximize", "minimize" or "fullscreen")
+/// -> return the new rect of the window
+pub(crate) fn resize_window(session_id: &str, command: &'static str) -> Command<WindowRect> {
+    debug!("resizing window ({}) on session with id {}", command, session_id);
+
+    Command::post(&format!("/session/{}/window/{}", session_id, command), "{}", move |json| {
+        if let Some(rect) = WindowRect::from_json(&json["value"]) {
+            debug!("window rect is {:?}", rect);
+            Ok(rect)
+        } else {
+            error!("response to {} window request was not understood: {}", command, json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
+
+pub(crate) fn get_page_source(session_id: &str) -> Command<String> {
     debug!("getting page source of active tab on session with id {}", session_id);
 
-    let json = get(&format!("http://localhost:4444/session/{}/source", session_id))?;
+    Command::get(&format!("/session/{}/source", session_id), |json| {
+        if json["value"].is_string() {
+            let source = json["value"].to_string();
+            debug!("page source is {}", source);
+            Ok(source)
+        } else {
+            error!("response to page source request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
 
-    if json["value"].is_string() {
-        let source = json["value"].to_string();
-        debug!("page source is {}", source);
-        return Ok(source)
-    } else {
-        error!("response to page source request was not understood: {}", json);
-        Err(WebdriverError::InvalidResponse)
-    }
-}
\ No newline at end of file
+/// get the status of the webdriver
+/// -> return true if the webdriver is ready to create sessions
+pub(crate) fn get_status() -> Command<bool> {
+    debug!("getting status of webdriver");
+
+    Command::get("/status", |json| {
+        if json["value"]["ready"].is_boolean() {
+            let ready = json["value"]["ready"].as_bool().unwrap();
+            debug!("webdriver ready: {} ({})", ready, json["value"]["message"]);
+            Ok(ready)
+        } else {
+            error!("response to status request was not understood: {}", json);
+            Err(ErrorKind::InvalidResponse.into())
+        }
+    })
+}
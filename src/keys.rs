@@ -0,0 +1,257 @@
+//! Special keys (Enter, arrows, modifiers...) that can be typed in elements or used in actions
+
+use std::fmt;
+
+/// A key of the [W3C key table](https://www.w3.org/TR/webdriver/#keyboard-actions).
+/// Each key is represented by a private-use codepoint understood by the webdriver.
+/// Keys can be formatted into strings.
+///
+/// # Example
+///
+/// ```rust
+/// use lw_webdriver::keys::Keys;
+///
+/// let text = format!("hello{}", Keys::Enter);
+/// assert_eq!(text, "hello\u{E007}");
+/// ```
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub enum Keys {
+    /// Release every modifier held in a [type_text()](../elements/struct.Element.html#method.type_text) call.
+    Null,
+    Cancel,
+    Help,
+    Backspace,
+    Tab,
+    Clear,
+    Return,
+    Enter,
+    Shift,
+    Control,
+    Alt,
+    Pause,
+    Escape,
+    Space,
+    PageUp,
+    PageDown,
+    End,
+    Home,
+    ArrowLeft,
+    ArrowUp,
+    ArrowRight,
+    ArrowDown,
+    Insert,
+    Delete,
+    Semicolon,
+    Equals,
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    Multiply,
+    Add,
+    Separator,
+    Subtract,
+    Decimal,
+    Divide,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    /// The Windows key or the Command key.
+    Meta,
+    ZenkakuHankaku,
+    RightShift,
+    RightControl,
+    RightAlt,
+    RightMeta,
+    NumpadPageUp,
+    NumpadPageDown,
+    NumpadEnd,
+    NumpadHome,
+    NumpadArrowLeft,
+    NumpadArrowUp,
+    NumpadArrowRight,
+    NumpadArrowDown,
+    NumpadInsert,
+    NumpadDelete
+}
+
+impl Keys {
+    pub fn to_char(self) -> char {
+        match self {
+            Keys::Null => '\u{E000}',
+            Keys::Cancel => '\u{E001}',
+            Keys::Help => '\u{E002}',
+            Keys::Backspace => '\u{E003}',
+            Keys::Tab => '\u{E004}',
+            Keys::Clear => '\u{E005}',
+            Keys::Return => '\u{E006}',
+            Keys::Enter => '\u{E007}',
+            Keys::Shift => '\u{E008}',
+            Keys::Control => '\u{E009}',
+            Keys::Alt => '\u{E00A}',
+            Keys::Pause => '\u{E00B}',
+            Keys::Escape => '\u{E00C}',
+            Keys::Space => '\u{E00D}',
+            Keys::PageUp => '\u{E00E}',
+            Keys::PageDown => '\u{E00F}',
+            Keys::End => '\u{E010}',
+            Keys::Home => '\u{E011}',
+            Keys::ArrowLeft => '\u{E012}',
+            Keys::ArrowUp => '\u{E013}',
+            Keys::ArrowRight => '\u{E014}',
+            Keys::ArrowDown => '\u{E015}',
+            Keys::Insert => '\u{E016}',
+            Keys::Delete => '\u{E017}',
+            Keys::Semicolon => '\u{E018}',
+            Keys::Equals => '\u{E019}',
+            Keys::Numpad0 => '\u{E01A}',
+            Keys::Numpad1 => '\u{E01B}',
+            Keys::Numpad2 => '\u{E01C}',
+            Keys::Numpad3 => '\u{E01D}',
+            Keys::Numpad4 => '\u{E01E}',
+            Keys::Numpad5 => '\u{E01F}',
+            Keys::Numpad6 => '\u{E020}',
+            Keys::Numpad7 => '\u{E021}',
+            Keys::Numpad8 => '\u{E022}',
+            Keys::Numpad9 => '\u{E023}',
+            Keys::Multiply => '\u{E024}',
+            Keys::Add => '\u{E025}',
+            Keys::Separator => '\u{E026}',
+            Keys::Subtract => '\u{E027}',
+            Keys::Decimal => '\u{E028}',
+            Keys::Divide => '\u{E029}',
+            Keys::F1 => '\u{E031}',
+            Keys::F2 => '\u{E032}',
+            Keys::F3 => '\u{E033}',
+            Keys::F4 => '\u{E034}',
+            Keys::F5 => '\u{E035}',
+            Keys::F6 => '\u{E036}',
+            Keys::F7 => '\u{E037}',
+            Keys::F8 => '\u{E038}',
+            Keys::F9 => '\u{E039}',
+            Keys::F10 => '\u{E03A}',
+            Keys::F11 => '\u{E03B}',
+            Keys::F12 => '\u{E03C}',
+            Keys::Meta => '\u{E03D}',
+            Keys::ZenkakuHankaku => '\u{E040}',
+            Keys::RightShift => '\u{E050}',
+            Keys::RightControl => '\u{E051}',
+            Keys::RightAlt => '\u{E052}',
+            Keys::RightMeta => '\u{E053}',
+            Keys::NumpadPageUp => '\u{E054}',
+            Keys::NumpadPageDown => '\u{E055}',
+            Keys::NumpadEnd => '\u{E056}',
+            Keys::NumpadHome => '\u{E057}',
+            Keys::NumpadArrowLeft => '\u{E058}',
+            Keys::NumpadArrowUp => '\u{E059}',
+            Keys::NumpadArrowRight => '\u{E05A}',
+            Keys::NumpadArrowDown => '\u{E05B}',
+            Keys::NumpadInsert => '\u{E05C}',
+            Keys::NumpadDelete => '\u{E05D}'
+        }
+    }
+
+    /// Return true for Shift, Control, Alt and Meta (left or right).
+    pub fn is_modifier(self) -> bool {
+        is_modifier(self.to_char())
+    }
+}
+
+impl From<Keys> for char {
+    fn from(key: Keys) -> char {
+        key.to_char()
+    }
+}
+
+impl fmt::Display for Keys {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+/// Return true if the codepoint is a modifier key
+pub(crate) fn is_modifier(key: char) -> bool {
+    matches!(key, '\u{E008}'..='\u{E00A}' | '\u{E03D}' | '\u{E050}'..='\u{E053}')
+}
+
+/// A sequence of text, special keys and key combinations.
+/// Modifiers of a [chord()](#method.chord) are held while its text is typed, then released.
+///
+/// # Example
+///
+/// ```rust
+/// use lw_webdriver::keys::{Keys, KeySequence};
+///
+/// // select everything, replace it and submit
+/// let keys = KeySequence::new()
+///     .chord(&[Keys::Control], "a")
+///     .text("new value")
+///     .key(Keys::Enter);
+/// assert_eq!(keys.as_str(), "\u{E009}a\u{E000}new value\u{E007}");
+///
+/// // element.type_text(keys.as_str()) or KeySource::new("keyboard").type_text(keys.as_str())
+/// ```
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct KeySequence {
+    text: String
+}
+
+impl KeySequence {
+    pub fn new() -> Self {
+        KeySequence::default()
+    }
+
+    pub fn text(mut self, text: &str) -> Self {
+        self.text.push_str(text);
+        self
+    }
+
+    pub fn key(mut self, key: Keys) -> Self {
+        self.text.push(key.to_char());
+        self
+    }
+
+    /// Hold modifiers while typing a text (for example Control + C).
+    pub fn chord(mut self, modifiers: &[Keys], text: &str) -> Self {
+        for modifier in modifiers {
+            self.text.push(modifier.to_char());
+        }
+        self.text.push_str(text);
+        self.text.push(Keys::Null.to_char());
+        self
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_string(self) -> String {
+        self.text
+    }
+}
+
+impl From<KeySequence> for String {
+    fn from(keys: KeySequence) -> String {
+        keys.text
+    }
+}
@@ -1,6 +1,6 @@
 //! This crate allows you to control a web browser (Firefox or chrome) easily. 
 //! It does not use selenium, which is much more lightweight.
-//! It only uses geckodriver or chromedriver (you have to download the one you want to use depending on your browser and place it in your program's directory).
+//! It only uses geckodriver or chromedriver (you have to download the one you want to use depending on your browser and place it in the current directory or in your PATH).
 //! This crate can launch the driver and kill his process after, but if one is already running, it will be used.  
 //! A lot of improvements can be done. Feel free to contribute.
 //! 
@@ -23,6 +23,10 @@
 //! # thread::sleep(Duration::from_secs(5));
 //! ```
 //! 
+//! # Async
+//! 
+//! The `async` feature adds async twins of sessions, tabs and elements in the [asynchronous](asynchronous/index.html) module, to use the crate from tokio applications.
+//! 
 //! # Running tests
 //! 
 //! Run tests one by one:
@@ -30,11 +34,36 @@
 //! ```ignore
 //! cargo test -- --test-threads=1
 //! ```
+//! 
+//! The `mock` feature provides a fake webdriver server ([MockServer](mock/struct.MockServer.html)), so tests can run without a browser:
+//! 
+//! ```sh
+//! cargo test --features mock mock_server
+//! ```
 
 pub mod session;
+pub mod capabilities;
+pub mod service;
 pub mod enums;
 pub mod tab;
 pub mod elements;
+pub mod script;
 pub mod timeouts;
+pub mod cookies;
+pub mod screenshot;
+pub mod print;
+pub mod actions;
+pub mod keys;
+pub mod alerts;
+pub mod frames;
+pub mod window;
+pub mod wait;
+pub mod transport;
+pub mod cassette;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "async")]
+pub mod asynchronous;
 pub mod error;
-mod http_requests;
\ No newline at end of file
+mod http_requests;
+mod base64;
\ No newline at end of file
@@ -0,0 +1,1167 @@
+//! A fake webdriver server, to run tests without a browser (requires the `mock` feature)
+
+use json::*;
+use std::result::Result;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use log::{debug, warn};
+use crate::window::WindowRect;
+use crate::cookies::{url_host, domain_matches};
+
+const ELEMENT_KEY: &str = "element-6066-11e4-a52e-4f735466cecf";
+/// A white 1x1 PNG image
+const PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAAC0lEQVR4nGP4DwQACfsD/fteaysAAAAASUVORK5CYII=";
+/// An empty PDF document
+const PDF: &str = "JVBERi0xLjQKMSAwIG9iaiA8PCAvVHlwZSAvQ2F0YWxvZyAvUGFnZXMgMiAwIFIgPj4gZW5kb2JqCjIgMCBvYmogPDwgL1R5cGUgL1BhZ2VzIC9LaWRzIFtdIC9Db3VudCAwID4+IGVuZG9iagp0cmFpbGVyIDw8IC9Sb290IDEgMCBSID4+CiUlRU9GCg==";
+
+/// An element of a [MockPage](struct.MockPage.html).
+/// Its visible text is its own text followed by the text of its children, on new lines.
+///
+/// Clicking on a link (`a` with an absolute or `/`-prefixed `href`) loads the target page, and clicking on a checkbox toggles its `checked` property.
+/// Text typed in an element is appended to its `value` property.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct MockElement {
+    tag: String,
+    attributes: Vec<(String, String)>,
+    properties: Vec<(String, JsonValue)>,
+    css: Vec<(String, String)>,
+    text: String,
+    rect: ((usize, usize), (usize, usize)),
+    enabled: bool,
+    displayed: bool,
+    children: Vec<MockElement>
+}
+
+impl MockElement {
+    pub fn new(tag: &str) -> Self {
+        MockElement {
+            tag: tag.to_ascii_lowercase(),
+            attributes: Vec::new(),
+            properties: Vec::new(),
+            css: Vec::new(),
+            text: String::new(),
+            rect: ((0, 0), (0, 0)),
+            enabled: true,
+            displayed: true,
+            children: Vec::new()
+        }
+    }
+
+    pub fn id(self, id: &str) -> Self {
+        self.attribute("id", id)
+    }
+
+    /// Set the `class` attribute (several classes can be separated by spaces).
+    pub fn class(self, class: &str) -> Self {
+        self.attribute("class", class)
+    }
+
+    pub fn attribute(mut self, name: &str, value: &str) -> Self {
+        self.attributes.retain(|(n, _)| n != name);
+        self.attributes.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn property<T: Into<JsonValue>>(mut self, name: &str, value: T) -> Self {
+        self.properties.retain(|(n, _)| n != name);
+        self.properties.push((name.to_string(), value.into()));
+        self
+    }
+
+    pub fn css(mut self, name: &str, value: &str) -> Self {
+        self.css.retain(|(n, _)| n != name);
+        self.css.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn text(mut self, text: &str) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    /// Set the position and the size returned by [Element::get_rect()](../elements/struct.Element.html#method.get_rect).
+    pub fn rect(mut self, position: (usize, usize), size: (usize, usize)) -> Self {
+        self.rect = (position, size);
+        self
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Hidden elements have no visible text and cannot be clicked.
+    pub fn displayed(mut self, displayed: bool) -> Self {
+        self.displayed = displayed;
+        self
+    }
+
+    /// Add a child element. The children of an `iframe` are the content of the frame.
+    pub fn child(mut self, child: MockElement) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    fn get_attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    fn get_property(&self, name: &str) -> JsonValue {
+        if let Some((_, value)) = self.properties.iter().find(|(n, _)| n == name) {
+            return value.clone();
+        }
+        match name {
+            "id" | "className" => self.get_attribute(if name == "id" { "id" } else { "class" }).unwrap_or("").into(),
+            "value" => match self.get_attribute("value") {
+                Some(value) => value.into(),
+                None if self.tag == "input" || self.tag == "textarea" => "".into(),
+                None => JsonValue::Null
+            },
+            "tagName" => self.tag.to_ascii_uppercase().into(),
+            _ => JsonValue::Null
+        }
+    }
+
+    fn set_property(&mut self, name: &str, value: JsonValue) {
+        self.properties.retain(|(n, _)| n != name);
+        self.properties.push((name.to_string(), value));
+    }
+}
+
+/// A page served by a [MockServer](struct.MockServer.html).
+///
+/// # Example
+///
+/// ```rust
+/// use lw_webdriver::mock::{MockPage, MockElement};
+///
+/// let page = MockPage::new("Log in")
+///     .element(MockElement::new("form").id("login")
+///         .child(MockElement::new("input").attribute("name", "email"))
+///         .child(MockElement::new("button").class("primary").text("Submit")))
+///     .element(MockElement::new("a").attribute("href", "/signup").text("Create an account"));
+/// ```
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct MockPage {
+    title: String,
+    elements: Vec<MockElement>
+}
+
+impl MockPage {
+    pub fn new(title: &str) -> Self {
+        MockPage {
+            title: title.to_string(),
+            elements: Vec::new()
+        }
+    }
+
+    /// Add an element to the body of the page.
+    pub fn element(mut self, element: MockElement) -> Self {
+        self.elements.push(element);
+        self
+    }
+}
+
+/// A fake W3C webdriver listening on a local port.
+/// It keeps a fake DOM of windows, elements and cookies, so that code using this crate can be tested offline.
+/// Pages are registered by url and loaded when a tab navigates to them (unknown urls load an empty page).
+///
+/// Scripts are not executed: they return `null` unless a handler is set with [on_script()](#method.on_script).
+/// CSS selectors support tag names, ids, classes, attributes and the descendant and child combinators.
+/// XPath selectors support `//tag` and `//tag[@name="value"]`.
+///
+/// The server stops when it is dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use lw_webdriver::{session::Session, enums::{Browser, Selector}};
+/// use lw_webdriver::mock::{MockServer, MockPage, MockElement};
+///
+/// let server = MockServer::start().unwrap();
+/// server.add_page("http://example.com/", MockPage::new("Example")
+///     .element(MockElement::new("h1").text("Example Domain")));
+///
+/// let mut session = Session::new_with_url(server.get_url(), Browser::Firefox, true).unwrap();
+/// session.tabs[0].navigate("http://example.com/").unwrap();
+/// assert_eq!(session.tabs[0].get_title().unwrap(), "Example");
+/// let title = session.tabs[0].find(Selector::Css, "h1").unwrap().unwrap();
+/// assert_eq!(title.get_text().unwrap(), "Example Domain");
+/// ```
+pub struct MockServer {
+    url: String,
+    port: u16,
+    state: Arc<Mutex<State>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>
+}
+
+impl MockServer {
+    /// Start a server on a free port.
+    pub fn start() -> Result<MockServer, io::Error> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let state = Arc::new(Mutex::new(State::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_state = Arc::clone(&state);
+        let thread_stop = Arc::clone(&stop);
+        let thread = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                match stream {
+                    Ok(stream) => {
+                        if let Err(e) = serve(stream, &thread_state) {
+                            warn!("mock server failed to answer a request: {}", e);
+                        }
+                    },
+                    Err(e) => warn!("mock server failed to accept a connection: {}", e)
+                }
+            }
+        });
+        debug!("mock server listening on port {}", port);
+
+        Ok(MockServer {
+            url: format!("http://127.0.0.1:{}", port),
+            port,
+            state,
+            stop,
+            thread: Some(thread)
+        })
+    }
+
+    /// Return the url to pass to [Session::new_with_url()](../session/struct.Session.html#method.new_with_url) or [SessionBuilder::webdriver_url()](../session/struct.SessionBuilder.html#method.webdriver_url).
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    /// Serve a page at this url. Tabs already displaying the url are not updated until they navigate again.
+    pub fn add_page(&self, url: &str, page: MockPage) {
+        let mut state = self.lock();
+        state.pages.retain(|(u, _)| u != url);
+        state.pages.push((url.to_string(), page));
+    }
+
+    /// Answer scripts with a function taking the script and its arguments.
+    pub fn on_script<F>(&self, handler: F) where F: Fn(&str, &[JsonValue]) -> JsonValue + Send + 'static {
+        self.lock().script_handler = Some(Box::new(handler));
+    }
+
+    /// Open an `alert()` dialog in every session.
+    /// Commands sent while it is open fail with an UnexpectedAlertOpen error which dismisses it.
+    pub fn open_alert(&self, text: &str) {
+        for session in &mut self.lock().sessions {
+            session.alert = Some(text.to_string());
+            session.prompt_text = None;
+        }
+    }
+
+    /// Return every request received, like `"POST /session/<id>/url"`.
+    pub fn get_requests(&self) -> Vec<String> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // wake up the listening thread
+        let _ = TcpStream::connect(("127.0.0.1", self.port));
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+type ScriptHandler = Box<dyn Fn(&str, &[JsonValue]) -> JsonValue + Send>;
+
+#[derive(Default)]
+struct State {
+    pages: Vec<(String, MockPage)>,
+    sessions: Vec<MockSession>,
+    script_handler: Option<ScriptHandler>,
+    requests: Vec<String>,
+    /// used to generate unique ids
+    counter: usize
+}
+
+struct MockSession {
+    id: String,
+    windows: Vec<Window>,
+    current: String,
+    cookies: Vec<JsonValue>,
+    timeouts: JsonValue,
+    rect: WindowRect,
+    alert: Option<String>,
+    prompt_text: Option<String>
+}
+
+struct Window {
+    handle: String,
+    history: Vec<String>,
+    position: usize,
+    page: Page,
+    /// iframes entered, from the top-level document
+    frames: Vec<usize>
+}
+
+struct Page {
+    title: String,
+    generation: usize,
+    /// elements in document order
+    nodes: Vec<Node>
+}
+
+struct Node {
+    element: MockElement,
+    parent: Option<usize>
+}
+
+impl Page {
+    fn load(pages: &[(String, MockPage)], url: &str, generation: usize) -> Page {
+        let mut page = Page {
+            title: String::new(),
+            generation,
+            nodes: Vec::new()
+        };
+        let mut body = MockElement::new("body");
+        if let Some((_, template)) = pages.iter().find(|(u, _)| u == url) {
+            page.title = template.title.clone();
+            body.children = template.elements.clone();
+        }
+        page.add(&MockElement::new("html").child(body), None);
+        page
+    }
+
+    fn add(&mut self, element: &MockElement, parent: Option<usize>) {
+        let index = self.nodes.len();
+        let mut node = element.clone();
+        node.children.clear();
+        self.nodes.push(Node { element: node, parent });
+        for child in &element.children {
+            self.add(child, Some(index));
+        }
+    }
+
+    /// The iframe containing a node (None for the top-level document)
+    fn document_of(&self, index: usize) -> Option<usize> {
+        let mut parent = self.nodes[index].parent;
+        while let Some(p) = parent {
+            if self.nodes[p].element.tag == "iframe" || self.nodes[p].element.tag == "frame" {
+                return Some(p);
+            }
+            parent = self.nodes[p].parent;
+        }
+        None
+    }
+
+    fn is_descendant(&self, index: usize, ancestor: usize) -> bool {
+        let mut parent = self.nodes[index].parent;
+        while let Some(p) = parent {
+            if p == ancestor {
+                return true;
+            }
+            parent = self.nodes[p].parent;
+        }
+        false
+    }
+
+    fn is_displayed(&self, index: usize) -> bool {
+        let mut current = Some(index);
+        while let Some(i) = current {
+            if !self.nodes[i].element.displayed {
+                return false;
+            }
+            current = self.nodes[i].parent;
+        }
+        true
+    }
+
+    fn text(&self, index: usize) -> String {
+        if !self.is_displayed(index) {
+            return String::new();
+        }
+        let mut texts = Vec::new();
+        for i in index..self.nodes.len() {
+            if (i == index || self.is_descendant(i, index)) && self.nodes[i].element.displayed && !self.nodes[i].element.text.is_empty() {
+                texts.push(self.nodes[i].element.text.as_str());
+            }
+        }
+        texts.join("\n")
+    }
+
+    /// Find the elements of the current document matching a selector, optionally inside an element
+    fn find(&self, frame: Option<usize>, scope: Option<usize>, using: &str, value: &str) -> Result<Vec<usize>, Failure> {
+        let selector = match using {
+            "css selector" => parse_css(value),
+            "xpath" => parse_xpath(value),
+            "tag name" => Some(vec![(Combinator::Descendant, Compound { tag: Some(value.to_ascii_lowercase()), ..Compound::default() })]),
+            "link text" | "partial link text" => None,
+            _ => return Err(Failure::new("invalid argument", &format!("unknown location strategy {}", using)))
+        };
+        if selector.is_none() && using != "link text" && using != "partial link text" {
+            return Err(Failure::new("invalid selector", &format!("the mock server does not support the selector {}", value)));
+        }
+
+        let mut found = Vec::new();
+        for index in 0..self.nodes.len() {
+            if self.document_of(index) != frame {
+                continue;
+            }
+            if let Some(scope) = scope {
+                if !self.is_descendant(index, scope) {
+                    continue;
+                }
+            }
+            let matches = match &selector {
+                Some(selector) => self.matches(index, selector, selector.len() - 1, frame),
+                None => {
+                    let element = &self.nodes[index].element;
+                    let text = self.text(index);
+                    element.tag == "a" && match using {
+                        "link text" => text.trim() == value,
+                        _ => text.contains(value)
+                    }
+                }
+            };
+            if matches {
+                found.push(index);
+            }
+        }
+        Ok(found)
+    }
+
+    fn matches(&self, index: usize, selector: &[(Combinator, Compound)], part: usize, frame: Option<usize>) -> bool {
+        if !selector[part].1.matches(&self.nodes[index].element) {
+            return false;
+        }
+        if part == 0 {
+            return true;
+        }
+        let mut parent = self.nodes[index].parent;
+        while let Some(p) = parent {
+            if Some(p) == frame {
+                return false;
+            }
+            if self.matches(p, selector, part - 1, frame) {
+                return true;
+            }
+            if selector[part].0 == Combinator::Child {
+                return false;
+            }
+            parent = self.nodes[p].parent;
+        }
+        false
+    }
+
+    fn to_html(&self) -> String {
+        let mut html = format!("<html><head><title>{}</title></head>", escape(&self.title));
+        // the body is the second node, after html
+        self.node_to_html(1, &mut html);
+        html.push_str("</html>");
+        html
+    }
+
+    fn node_to_html(&self, index: usize, html: &mut String) {
+        let element = &self.nodes[index].element;
+        html.push('<');
+        html.push_str(&element.tag);
+        for (name, value) in &element.attributes {
+            html.push_str(&format!(" {}=\"{}\"", name, escape(value)));
+        }
+        html.push('>');
+        html.push_str(&escape(&element.text));
+        for (child, node) in self.nodes.iter().enumerate().skip(index + 1) {
+            if node.parent == Some(index) {
+                self.node_to_html(child, html);
+            }
+        }
+        html.push_str(&format!("</{}>", element.tag));
+    }
+}
+
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+enum Combinator {
+    Descendant,
+    Child
+}
+
+#[derive(Debug)]
+#[derive(Default)]
+struct Compound {
+    tag: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+    attributes: Vec<(String, Option<String>)>
+}
+
+impl Compound {
+    fn matches(&self, element: &MockElement) -> bool {
+        if let Some(tag) = &self.tag {
+            if tag != "*" && *tag != element.tag {
+                return false;
+            }
+        }
+        if let Some(id) = &self.id {
+            if element.get_attribute("id") != Some(id.as_str()) {
+                return false;
+            }
+        }
+        let classes: Vec<&str> = element.get_attribute("class").unwrap_or("").split_whitespace().collect();
+        if !self.classes.iter().all(|class| classes.contains(&class.as_str())) {
+            return false;
+        }
+        self.attributes.iter().all(|(name, value)| match (element.get_attribute(name), value) {
+            (Some(_), None) => true,
+            (Some(actual), Some(value)) => actual == value,
+            (None, _) => false
+        })
+    }
+}
+
+/// Parse a simple CSS selector (compound selectors with descendant and child combinators)
+fn parse_css(selector: &str) -> Option<Vec<(Combinator, Compound)>> {
+    let mut parts = Vec::new();
+    let mut combinator = Combinator::Descendant;
+    let mut current = String::new();
+    let mut in_brackets = false;
+    for c in selector.trim().chars() {
+        match c {
+            '[' => { in_brackets = true; current.push(c) },
+            ']' => { in_brackets = false; current.push(c) },
+            ' ' | '>' if !in_brackets => {
+                if !current.is_empty() {
+                    parts.push((combinator, parse_compound(&current)?));
+                    current.clear();
+                    combinator = Combinator::Descendant;
+                }
+                if c == '>' {
+                    if parts.is_empty() {
+                        return None;
+                    }
+                    combinator = Combinator::Child;
+                }
+            },
+            _ => current.push(c)
+        }
+    }
+    if current.is_empty() {
+        return None;
+    }
+    parts.push((combinator, parse_compound(&current)?));
+    Some(parts)
+}
+
+fn parse_compound(text: &str) -> Option<Compound> {
+    fn is_name(c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '-' || c == '_'
+    }
+
+    let mut compound = Compound::default();
+    let mut rest = text;
+    let tag_length = rest.find(|c: char| !is_name(c) && c != '*').unwrap_or(rest.len());
+    if tag_length > 0 {
+        compound.tag = Some(rest[..tag_length].to_ascii_lowercase());
+        rest = &rest[tag_length..];
+    }
+    while !rest.is_empty() {
+        let first = rest.chars().next()?;
+        match first {
+            '#' | '.' => {
+                let length = rest[1..].find(|c: char| !is_name(c)).unwrap_or(rest.len() - 1);
+                if length == 0 {
+                    return None;
+                }
+                let name = rest[1..1 + length].to_string();
+                if first == '#' {
+                    compound.id = Some(name);
+                } else {
+                    compound.classes.push(name);
+                }
+                rest = &rest[1 + length..];
+            },
+            '[' => {
+                let end = rest.find(']')?;
+                let content = &rest[1..end];
+                let attribute = match content.find('=') {
+                    Some(position) => {
+                        let value = content[position + 1..].trim().trim_matches(|c| c == '"' || c == '\'');
+                        (content[..position].trim().to_string(), Some(value.to_string()))
+                    },
+                    None => (content.trim().to_string(), None)
+                };
+                if attribute.0.is_empty() || !attribute.0.chars().all(is_name) {
+                    return None;
+                }
+                compound.attributes.push(attribute);
+                rest = &rest[end + 1..];
+            },
+            _ => return None
+        }
+    }
+    Some(compound)
+}
+
+/// Parse a simple XPath selector (`//tag` or `//tag[@name="value"]`)
+fn parse_xpath(selector: &str) -> Option<Vec<(Combinator, Compound)>> {
+    let rest = selector.trim().strip_prefix("//")?;
+    let (tag, predicate) = match rest.find('[') {
+        Some(position) => (&rest[..position], Some(rest[position..].strip_prefix("[@")?.strip_suffix(']')?)),
+        None => (rest, None)
+    };
+    if tag.is_empty() || !tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '*') {
+        return None;
+    }
+    let mut compound = Compound { tag: Some(tag.to_ascii_lowercase()), ..Compound::default() };
+    if let Some(predicate) = predicate {
+        let position = predicate.find('=')?;
+        let value = predicate[position + 1..].trim();
+        if value.len() < 2 || !((value.starts_with('"') && value.ends_with('"')) || (value.starts_with('\'') && value.ends_with('\''))) {
+            return None;
+        }
+        compound.attributes.push((predicate[..position].trim().to_string(), Some(value[1..value.len() - 1].to_string())));
+    }
+    Some(vec![(Combinator::Descendant, compound)])
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = text.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// An error returned to the client
+struct Failure {
+    error: &'static str,
+    message: String,
+    data: JsonValue
+}
+
+impl Failure {
+    fn new(error: &'static str, message: &str) -> Self {
+        Failure {
+            error,
+            message: message.to_string(),
+            data: JsonValue::Null
+        }
+    }
+
+    fn status(&self) -> u16 {
+        match self.error {
+            "invalid argument" | "invalid selector" | "element not interactable" | "invalid cookie domain" => 400,
+            "no such element" | "no such window" | "no such frame" | "no such alert" | "no such cookie"
+                | "stale element reference" | "invalid session id" | "unknown command" => 404,
+            _ => 500
+        }
+    }
+}
+
+type Reply = Result<JsonValue, Failure>;
+
+/// Read a request, answer it and close the connection
+fn serve(stream: TcpStream, state: &Mutex<State>) -> Result<(), io::Error> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line)? == 0 {
+        // connection used to wake the server up
+        return Ok(());
+    }
+    let mut words = request_line.split_whitespace();
+    let method = words.next().unwrap_or("").to_string();
+    let path = words.next().unwrap_or("").to_string();
+
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some(position) = header.find(':') {
+            if header[..position].trim().eq_ignore_ascii_case("content-length") {
+                length = header[position + 1..].trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    let reply = {
+        let mut state = state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.requests.push(format!("{} {}", method, path));
+        let body = String::from_utf8_lossy(&body);
+        match json::parse(if body.trim().is_empty() { "{}" } else { &body }) {
+            Ok(body) => handle(&mut state, &method, &path, &body),
+            Err(e) => Err(Failure::new("invalid argument", &format!("the body is not valid json: {}", e)))
+        }
+    };
+    let (status, value) = match reply {
+        Ok(value) => (200, value),
+        Err(failure) => (failure.status(), object!{
+            "error" => failure.error,
+            "message" => failure.message.as_str(),
+            "stacktrace" => "",
+            "data" => failure.data
+        })
+    };
+    debug!("mock server answered {} {} with {} {}", method, path, status, value);
+
+    let body = object!{ "value" => value }.dump();
+    let mut stream = stream;
+    write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n{}",
+        status, if status == 200 { "OK" } else { "Error" }, body.len(), body)?;
+    stream.flush()
+}
+
+fn handle(state: &mut State, method: &str, path: &str, body: &JsonValue) -> Reply {
+    let path: Vec<String> = path.split('?').next().unwrap_or("").split('/').filter(|p| !p.is_empty()).map(decode).collect();
+    let path: Vec<&str> = path.iter().map(|p| p.as_str()).collect();
+
+    match (method, path.as_slice()) {
+        ("GET", ["status"]) => Ok(object!{ "ready" => true, "message" => "mock server ready" }),
+        ("POST", ["session"]) => new_session(state, body),
+        ("DELETE", ["session", id]) => {
+            let count = state.sessions.len();
+            state.sessions.retain(|s| s.id != *id);
+            if state.sessions.len() == count {
+                return Err(Failure::new("invalid session id", &format!("no session with id {}", id)));
+            }
+            Ok(JsonValue::Null)
+        },
+        (_, ["session", id, command @ ..]) => {
+            let State { pages, sessions, script_handler, counter, .. } = state;
+            let session = match sessions.iter_mut().find(|s| s.id == *id) {
+                Some(session) => session,
+                None => return Err(Failure::new("invalid session id", &format!("no session with id {}", id)))
+            };
+            session_command(session, pages, script_handler, counter, method, command, body)
+        },
+        _ => Err(Failure::new("unknown command", &format!("{} {} is not supported by the mock server", method, path.join("/"))))
+    }
+}
+
+fn new_session(state: &mut State, body: &JsonValue) -> Reply {
+    state.counter += 1;
+    let id = format!("session-{}", state.counter);
+    state.counter += 1;
+    let handle = format!("window-{}", state.counter);
+    state.counter += 1;
+    let page = Page::load(&state.pages, "about:blank", state.counter);
+
+    let browser_name = body["capabilities"]["alwaysMatch"]["browserName"].as_str().unwrap_or("mock").to_string();
+    state.sessions.push(MockSession {
+        id: id.clone(),
+        windows: vec![Window { handle: handle.clone(), history: vec!["about:blank".to_string()], position: 0, page, frames: Vec::new() }],
+        current: handle,
+        cookies: Vec::new(),
+        timeouts: object!{ "script" => 30000, "pageLoad" => 300_000, "implicit" => 0 },
+        rect: WindowRect::new(0, 0, 1280, 800),
+        alert: None,
+        prompt_text: None
+    });
+
+    Ok(object!{
+        "sessionId" => id,
+        "capabilities" => object!{
+            "browserName" => browser_name,
+            "browserVersion" => "0.0",
+            "platformName" => body["capabilities"]["alwaysMatch"]["platformName"].as_str().unwrap_or("linux"),
+            "acceptInsecureCerts" => false,
+            "setWindowRect" => true
+        }
+    })
+}
+
+fn current_window(session: &mut MockSession) -> Result<&mut Window, Failure> {
+    let current = session.current.clone();
+    session.windows.iter_mut().find(|w| w.handle == current).ok_or_else(|| Failure::new("no such window", "the current window was closed"))
+}
+
+/// Find the node of an element reference in the current window
+fn node(window: &Window, id: &str) -> Result<usize, Failure> {
+    let mut parts = id.splitn(2, '-');
+    let generation: Option<usize> = parts.next().and_then(|g| g.parse().ok());
+    let index: Option<usize> = parts.next().and_then(|i| i.parse().ok());
+    match (generation, index) {
+        (Some(generation), Some(index)) if generation == window.page.generation && index < window.page.nodes.len() => Ok(index),
+        (Some(generation), Some(_)) if generation < window.page.generation => {
+            Err(Failure::new("stale element reference", &format!("the element {} is not attached to the page anymore", id)))
+        },
+        _ => Err(Failure::new("no such element", &format!("unknown element {}", id)))
+    }
+}
+
+fn reference(window: &Window, index: usize) -> JsonValue {
+    let mut reference = JsonValue::new_object();
+    reference[ELEMENT_KEY] = format!("{}-{}", window.page.generation, index).into();
+    reference
+}
+
+fn navigate(window: &mut Window, pages: &[(String, MockPage)], counter: &mut usize, url: &str) {
+    *counter += 1;
+    window.page = Page::load(pages, url, *counter);
+    window.frames.clear();
+}
+
+fn session_command(session: &mut MockSession, pages: &[(String, MockPage)], script_handler: &Option<ScriptHandler>, counter: &mut usize, method: &str, command: &[&str], body: &JsonValue) -> Reply {
+    // dialogs block every command which handles user prompts
+    let ignores_prompts = matches!(command, ["timeouts"] | ["window"] | ["window", "handles"] | ["alert", ..]);
+    if let (Some(text), false) = (&session.alert, ignores_prompts) {
+        let mut failure = Failure::new("unexpected alert open", &format!("dismissed user prompt dialog: {}", text));
+        failure.data = object!{ "text" => text.as_str() };
+        session.alert = None;
+        return Err(failure);
+    }
+
+    match (method, command) {
+        ("GET", ["timeouts"]) => Ok(session.timeouts.clone()),
+        ("POST", ["timeouts"]) => {
+            for (name, value) in body.entries() {
+                session.timeouts[name] = value.clone();
+            }
+            Ok(JsonValue::Null)
+        },
+        ("GET", ["window"]) => Ok(current_window(session)?.handle.as_str().into()),
+        ("POST", ["window"]) => {
+            let handle = body["handle"].as_str().unwrap_or("");
+            if !session.windows.iter().any(|w| w.handle == handle) {
+                return Err(Failure::new("no such window", &format!("no window with handle {}", handle)));
+            }
+            session.current = handle.to_string();
+            Ok(JsonValue::Null)
+        },
+        ("DELETE", ["window"]) => {
+            current_window(session)?;
+            let current = session.current.clone();
+            session.windows.retain(|w| w.handle != current);
+            Ok(session.windows.iter().map(|w| JsonValue::from(w.handle.as_str())).collect::<Vec<JsonValue>>().into())
+        },
+        ("GET", ["window", "handles"]) => {
+            Ok(session.windows.iter().map(|w| JsonValue::from(w.handle.as_str())).collect::<Vec<JsonValue>>().into())
+        },
+        ("POST", ["window", "new"]) => {
+            *counter += 1;
+            let handle = format!("window-{}", counter);
+            *counter += 1;
+            let page = Page::load(pages, "about:blank", *counter);
+            session.windows.push(Window { handle: handle.clone(), history: vec!["about:blank".to_string()], position: 0, page, frames: Vec::new() });
+            Ok(object!{ "handle" => handle, "type" => "tab" })
+        },
+        ("GET", ["window", "rect"]) => Ok(session.rect.to_json()),
+        ("POST", ["window", "rect"]) => {
+            for (name, value) in body.entries() {
+                match (name, value.as_f64()) {
+                    ("x", Some(x)) => session.rect.x = x as i32,
+                    ("y", Some(y)) => session.rect.y = y as i32,
+                    ("width", Some(width)) => session.rect.width = width as u32,
+                    ("height", Some(height)) => session.rect.height = height as u32,
+                    (_, None) if value.is_null() => (),
+                    _ => return Err(Failure::new("invalid argument", &format!("invalid window rect field {}", name)))
+                }
+            }
+            Ok(session.rect.to_json())
+        },
+        ("POST", ["window", "maximize"]) | ("POST", ["window", "fullscreen"]) => {
+            session.rect = WindowRect::new(0, 0, 1920, 1080);
+            Ok(session.rect.to_json())
+        },
+        ("POST", ["window", "minimize"]) => Ok(session.rect.to_json()),
+        ("GET", ["alert", "text"]) => match &session.alert {
+            Some(text) => Ok(text.as_str().into()),
+            None => Err(Failure::new("no such alert", "no dialog is open"))
+        },
+        ("POST", ["alert", "text"]) => match &session.alert {
+            Some(_) => {
+                session.prompt_text = body["text"].as_str().map(|t| t.to_string());
+                Ok(JsonValue::Null)
+            },
+            None => Err(Failure::new("no such alert", "no dialog is open"))
+        },
+        ("POST", ["alert", "accept"]) | ("POST", ["alert", "dismiss"]) => match session.alert.take() {
+            Some(_) => Ok(JsonValue::Null),
+            None => Err(Failure::new("no such alert", "no dialog is open"))
+        },
+        ("GET", ["cookie"]) => {
+            let url = current_url(current_window(session)?);
+            let host = url_host(&url).to_string();
+            Ok(session.cookies.iter().filter(|c| domain_matches(c["domain"].as_str(), &host)).cloned().collect::<Vec<JsonValue>>().into())
+        },
+        ("GET", ["cookie", name]) => {
+            let url = current_url(current_window(session)?);
+            let host = url_host(&url).to_string();
+            session.cookies.iter().find(|c| c["name"] == *name && domain_matches(c["domain"].as_str(), &host)).cloned()
+                .ok_or_else(|| Failure::new("no such cookie", &format!("no cookie named {}", name)))
+        },
+        ("POST", ["cookie"]) => {
+            let url = current_url(current_window(session)?);
+            let host = url_host(&url).to_string();
+            let mut cookie = body["cookie"].clone();
+            if !cookie["name"].is_string() || !cookie["value"].is_string() {
+                return Err(Failure::new("invalid argument", "a cookie needs a name and a value"));
+            }
+            if cookie["domain"].is_null() {
+                cookie["domain"] = host.as_str().into();
+            } else if let Some(domain) = cookie["domain"].as_str().filter(|d| !d.starts_with('.')) {
+                // like browsers, an explicit domain also matches subdomains
+                cookie["domain"] = format!(".{}", domain).into();
+            }
+            if !domain_matches(cookie["domain"].as_str(), &host) {
+                return Err(Failure::new("invalid cookie domain", &format!("the cookie domain {} does not match {}", cookie["domain"], host)));
+            }
+            if cookie["path"].is_null() {
+                cookie["path"] = "/".into();
+            }
+            session.cookies.retain(|c| c["name"] != cookie["name"] || c["domain"] != cookie["domain"]);
+            session.cookies.push(cookie);
+            Ok(JsonValue::Null)
+        },
+        ("DELETE", ["cookie"]) => {
+            session.cookies.clear();
+            Ok(JsonValue::Null)
+        },
+        ("DELETE", ["cookie", name]) => {
+            session.cookies.retain(|c| c["name"] != *name);
+            Ok(JsonValue::Null)
+        },
+        ("POST", ["actions"]) => {
+            if !body["actions"].is_array() {
+                return Err(Failure::new("invalid argument", "actions must be an array"));
+            }
+            Ok(JsonValue::Null)
+        },
+        ("DELETE", ["actions"]) => Ok(JsonValue::Null),
+        ("POST", ["execute", "sync"]) | ("POST", ["execute", "async"]) => {
+            let script = body["script"].as_str().unwrap_or("");
+            let args: Vec<JsonValue> = body["args"].members().cloned().collect();
+            // scripts used by the crate itself
+            if script == "return [window.innerWidth, window.innerHeight];" {
+                return Ok(array![session.rect.width, session.rect.height]);
+            }
+            if let ("arguments[0].click();", Some(element)) = (script, args.first().and_then(|a| a[ELEMENT_KEY].as_str())) {
+                let window = current_window(session)?;
+                let index = node(window, element)?;
+                return element_command(window, pages, counter, index, "POST", &["click"], body);
+            }
+            Ok(match script_handler {
+                Some(handler) => handler(script, &args),
+                None => JsonValue::Null
+            })
+        },
+        ("GET", ["screenshot"]) => Ok(PNG.into()),
+        ("POST", ["print"]) => Ok(PDF.into()),
+        _ => window_command(current_window(session)?, pages, counter, method, command, body)
+    }
+}
+
+fn current_url(window: &Window) -> String {
+    window.history[window.position].clone()
+}
+
+fn window_command(window: &mut Window, pages: &[(String, MockPage)], counter: &mut usize, method: &str, command: &[&str], body: &JsonValue) -> Reply {
+    match (method, command) {
+        ("POST", ["url"]) => {
+            let url = match body["url"].as_str() {
+                Some(url) => url.to_string(),
+                None => return Err(Failure::new("invalid argument", "missing url"))
+            };
+            window.history.truncate(window.position + 1);
+            window.history.push(url.clone());
+            window.position += 1;
+            navigate(window, pages, counter, &url);
+            Ok(JsonValue::Null)
+        },
+        ("GET", ["url"]) => Ok(current_url(window).into()),
+        ("GET", ["title"]) => Ok(window.page.title.as_str().into()),
+        ("GET", ["source"]) => Ok(window.page.to_html().into()),
+        ("POST", ["back"]) | ("POST", ["forward"]) | ("POST", ["refresh"]) => {
+            match command[0] {
+                "back" if window.position > 0 => window.position -= 1,
+                "forward" if window.position + 1 < window.history.len() => window.position += 1,
+                _ => ()
+            }
+            let url = current_url(window);
+            navigate(window, pages, counter, &url);
+            Ok(JsonValue::Null)
+        },
+        ("POST", ["frame"]) => {
+            let id = &body["id"];
+            if id.is_null() {
+                window.frames.clear();
+                return Ok(JsonValue::Null);
+            }
+            let current = window.frames.last().copied();
+            let frame = if let Some(index) = id.as_usize() {
+                let frames: Vec<usize> = (0..window.page.nodes.len())
+                    .filter(|i| window.page.document_of(*i) == current && matches!(window.page.nodes[*i].element.tag.as_str(), "iframe" | "frame"))
+                    .collect();
+                frames.get(index).copied()
+            } else if let Some(element) = id[ELEMENT_KEY].as_str() {
+                let index = node(window, element)?;
+                match window.page.nodes[index].element.tag.as_str() {
+                    "iframe" | "frame" => Some(index),
+                    _ => None
+                }
+            } else {
+                return Err(Failure::new("invalid argument", "the frame id must be null, a number or an element"));
+            };
+            match frame {
+                Some(frame) => {
+                    window.frames.push(frame);
+                    Ok(JsonValue::Null)
+                },
+                None => Err(Failure::new("no such frame", &format!("no frame matching {}", id)))
+            }
+        },
+        ("POST", ["frame", "parent"]) => {
+            window.frames.pop();
+            Ok(JsonValue::Null)
+        },
+        ("POST", ["element"]) | ("POST", ["elements"]) => {
+            let frame = window.frames.last().copied();
+            let found = window.page.find(frame, None, body["using"].as_str().unwrap_or(""), body["value"].as_str().unwrap_or(""))?;
+            found_reply(window, command[0] == "elements", found, body)
+        },
+        ("POST", ["element", id, search]) if *search == "element" || *search == "elements" => {
+            let scope = node(window, id)?;
+            let frame = window.frames.last().copied();
+            let found = window.page.find(frame, Some(scope), body["using"].as_str().unwrap_or(""), body["value"].as_str().unwrap_or(""))?;
+            found_reply(window, *search == "elements", found, body)
+        },
+        (_, ["element", id, ..]) => {
+            let index = node(window, id)?;
+            element_command(window, pages, counter, index, method, &command[2..], body)
+        },
+        _ => Err(Failure::new("unknown command", &format!("{} {} is not supported by the mock server", method, command.join("/"))))
+    }
+}
+
+fn found_reply(window: &Window, all: bool, found: Vec<usize>, body: &JsonValue) -> Reply {
+    if all {
+        return Ok(found.into_iter().map(|index| reference(window, index)).collect::<Vec<JsonValue>>().into());
+    }
+    match found.first() {
+        Some(index) => Ok(reference(window, *index)),
+        None => Err(Failure::new("no such element", &format!("unable to locate element: {}", body["value"])))
+    }
+}
+
+fn element_command(window: &mut Window, pages: &[(String, MockPage)], counter: &mut usize, index: usize, method: &str, command: &[&str], body: &JsonValue) -> Reply {
+    let displayed = window.page.is_displayed(index);
+    let text = window.page.text(index);
+    let element = &mut window.page.nodes[index].element;
+
+    match (method, command) {
+        ("GET", ["text"]) => Ok(text.into()),
+        ("GET", ["name"]) => Ok(element.tag.as_str().into()),
+        ("GET", ["attribute", name]) => Ok(element.get_attribute(name).map(JsonValue::from).unwrap_or(JsonValue::Null)),
+        ("GET", ["property", name]) => Ok(element.get_property(name)),
+        ("GET", ["css", name]) => {
+            let value = element.css.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str());
+            Ok(match (value, *name) {
+                (Some(value), _) => value.into(),
+                (None, "display") if !displayed => "none".into(),
+                (None, _) => "".into()
+            })
+        },
+        ("GET", ["rect"]) => Ok(object!{
+            "x" => (element.rect.0).0,
+            "y" => (element.rect.0).1,
+            "width" => (element.rect.1).0,
+            "height" => (element.rect.1).1
+        }),
+        ("GET", ["enabled"]) => Ok(element.enabled.into()),
+        ("GET", ["displayed"]) => Ok(displayed.into()),
+        ("GET", ["screenshot"]) => Ok(PNG.into()),
+        ("POST", ["click"]) => {
+            if !displayed {
+                return Err(Failure::new("element not interactable", "the element is not displayed"));
+            }
+            if !element.enabled {
+                return Ok(JsonValue::Null);
+            }
+            if element.tag == "input" && element.get_attribute("type") == Some("checkbox") {
+                let checked = element.get_property("checked").as_bool().unwrap_or_else(|| element.get_attribute("checked").is_some());
+                element.set_property("checked", (!checked).into());
+            }
+            let href = match element.tag.as_str() {
+                "a" => element.get_attribute("href").map(|h| h.to_string()),
+                _ => None
+            };
+            if let Some(href) = href {
+                let url = current_url(window);
+                let target = match href.strip_prefix('/') {
+                    Some(path) => {
+                        let origin_start = url.find("://").map(|p| p + 3).unwrap_or(0);
+                        let origin_end = url[origin_start..].find('/').map(|p| p + origin_start).unwrap_or(url.len());
+                        format!("{}/{}", &url[..origin_end], path)
+                    },
+                    None => href
+                };
+                if target.contains("://") {
+                    window.history.truncate(window.position + 1);
+                    window.history.push(target.clone());
+                    window.position += 1;
+                    navigate(window, pages, counter, &target);
+                }
+            }
+            Ok(JsonValue::Null)
+        },
+        ("POST", ["value"]) => {
+            if !displayed || !element.enabled {
+                return Err(Failure::new("element not interactable", "the element is not displayed or disabled"));
+            }
+            let mut value = match element.get_property("value") {
+                JsonValue::Null => String::new(),
+                value => value.to_string()
+            };
+            for c in body["text"].as_str().unwrap_or("").chars() {
+                match c {
+                    // backspace
+                    '\u{E003}' => { value.pop(); },
+                    '\u{E000}'..='\u{F8FF}' => (),
+                    c => value.push(c)
+                }
+            }
+            element.set_property("value", value.into());
+            Ok(JsonValue::Null)
+        },
+        _ => Err(Failure::new("unknown command", &format!("{} element/{} is not supported by the mock server", method, command.join("/"))))
+    }
+}
@@ -0,0 +1,150 @@
+//! Options used to print a page to PDF
+
+use json::*;
+
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub enum Orientation {
+    Portrait,
+    Landscape
+}
+
+impl Orientation {
+    pub fn to_string(self) -> &'static str {
+        match self {
+            Orientation::Portrait => "portrait",
+            Orientation::Landscape => "landscape"
+        }
+    }
+}
+
+/// Pages to print, numbered from 1.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub enum PageRange {
+    /// A single page.
+    Page(usize),
+    /// Every page from the first number to the second one (included).
+    Range(usize, usize)
+}
+
+impl PageRange {
+    pub fn to_json(self) -> JsonValue {
+        match self {
+            PageRange::Page(page) => page.into(),
+            PageRange::Range(first, last) => format!("{}-{}", first, last).into()
+        }
+    }
+}
+
+/// Size of the paper in centimeters.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub struct PageSize {
+    pub width: f64,
+    pub height: f64
+}
+
+impl PageSize {
+    /// 21.59 x 27.94 cm
+    pub const LETTER: PageSize = PageSize { width: 21.59, height: 27.94 };
+    /// 21.0 x 29.7 cm
+    pub const A4: PageSize = PageSize { width: 21.0, height: 29.7 };
+}
+
+/// Margins of the page in centimeters.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub struct Margins {
+    pub top: f64,
+    pub bottom: f64,
+    pub left: f64,
+    pub right: f64
+}
+
+impl Margins {
+    /// The same margin on every side.
+    pub fn uniform(margin: f64) -> Self {
+        Margins {
+            top: margin,
+            bottom: margin,
+            left: margin,
+            right: margin
+        }
+    }
+}
+
+/// Options of [Tab::print_pdf()](../tab/struct.Tab.html#method.print_pdf).
+/// The default values are the ones of the W3C specification: portrait letter pages with 1 cm margins, without background.
+///
+/// # Example
+///
+/// ```rust
+/// use lw_webdriver::print::{PrintOptions, Orientation, PageSize, Margins, PageRange};
+///
+/// let options = PrintOptions {
+///     orientation: Orientation::Landscape,
+///     page: PageSize::A4,
+///     margin: Margins::uniform(0.5),
+///     background: true,
+///     page_ranges: vec![PageRange::Page(1), PageRange::Range(3, 5)],
+///     ..PrintOptions::default()
+/// };
+/// assert_eq!(options.to_json()["pageRanges"][1], "3-5");
+/// ```
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct PrintOptions {
+    pub orientation: Orientation,
+    /// Between 0.1 and 2.
+    pub scale: f64,
+    /// Print background graphics.
+    pub background: bool,
+    pub page: PageSize,
+    pub margin: Margins,
+    /// Pages to print. Every page is printed if empty.
+    pub page_ranges: Vec<PageRange>,
+    /// Resize the content to match the page width.
+    pub shrink_to_fit: bool
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        PrintOptions {
+            orientation: Orientation::Portrait,
+            scale: 1.0,
+            background: false,
+            page: PageSize::LETTER,
+            margin: Margins::uniform(1.0),
+            page_ranges: Vec::new(),
+            shrink_to_fit: true
+        }
+    }
+}
+
+impl PrintOptions {
+    pub fn to_json(&self) -> JsonValue {
+        object!{
+            "orientation" => self.orientation.to_string(),
+            "scale" => self.scale,
+            "background" => self.background,
+            "page" => object!{
+                "width" => self.page.width,
+                "height" => self.page.height
+            },
+            "margin" => object!{
+                "top" => self.margin.top,
+                "bottom" => self.margin.bottom,
+                "left" => self.margin.left,
+                "right" => self.margin.right
+            },
+            "pageRanges" => self.page_ranges.iter().map(|range| range.to_json()).collect::<Vec<JsonValue>>(),
+            "shrinkToFit" => self.shrink_to_fit
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! Screenshots of tabs and elements
+
+use std::{fs, io};
+use std::path::Path;
+
+/// A PNG image captured by [Tab::screenshot()](../tab/struct.Tab.html#method.screenshot) or [Element::screenshot()](../elements/struct.Element.html#method.screenshot).
+///
+/// # Example
+///
+/// ```rust
+/// # use lw_webdriver::{session::Session, enums::Browser};
+/// let mut session = Session::new(Browser::Firefox, false).unwrap();
+/// session.tabs[0].navigate("http://example.com/").unwrap();
+///
+/// let screenshot = session.tabs[0].screenshot().unwrap();
+/// screenshot.save("example.png").unwrap();
+/// # std::fs::remove_file("example.png").unwrap();
+/// ```
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Screenshot {
+    png: Vec<u8>
+}
+
+impl Screenshot {
+    pub(crate) fn new(png: Vec<u8>) -> Self {
+        Screenshot {
+            png
+        }
+    }
+
+    /// Return the content of the PNG file.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.png
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.png
+    }
+
+    /// Write the PNG file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        fs::write(path, &self.png)
+    }
+}
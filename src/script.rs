@@ -0,0 +1,172 @@
+//! Values returned by scripts executed in a tab
+
+use json::*;
+use std::sync::Arc;
+use crate::elements::{Element, ElementRef};
+use crate::enums::WebdriverObject;
+use crate::http_requests::Client;
+
+/// A value returned by a script.
+/// This is a JSON value, except that web elements are turned into [Elements](../elements/struct.Element.html).
+/// Scripts executed in an async tab return async elements instead (`ScriptValue<AsyncElement>`).
+///
+/// # Example
+///
+/// ```rust
+/// # use lw_webdriver::{session::Session, enums::Browser};
+/// let mut session = Session::new(Browser::Firefox, false).unwrap();
+/// session.tabs[0].navigate("http://example.com/").unwrap();
+///
+/// let count = session.tabs[0].execute_script("return document.querySelectorAll('p').length;", vec![]).unwrap();
+/// assert_eq!(count.as_usize(), Some(2));
+///
+/// let link = session.tabs[0].execute_script("return document.querySelector('a');", vec![]).unwrap();
+/// assert_eq!(link.into_element().unwrap().get_tag_name().unwrap(), "a");
+/// ```
+#[derive(Debug)]
+#[derive(Clone)]
+pub enum ScriptValue<E = Element> {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<ScriptValue<E>>),
+    Object(Vec<(String, ScriptValue<E>)>),
+    Element(E)
+}
+
+impl ScriptValue {
+    pub(crate) fn from_json(json: &JsonValue, session_id: &Arc<String>, tab_id: &Arc<String>, client: &Arc<Client>) -> ScriptValue {
+        ScriptValue::from_json_with(json, &|id| Element::new(id, Arc::clone(session_id), Arc::clone(tab_id), Arc::clone(client)))
+    }
+}
+
+impl<E: WebdriverObject> ScriptValue<E> {
+    /// Convert a JSON value, turning web element references into elements with `element`
+    pub(crate) fn from_json_with(json: &JsonValue, element: &dyn Fn(String) -> E) -> ScriptValue<E> {
+        match json {
+            JsonValue::Null => ScriptValue::Null,
+            JsonValue::Boolean(value) => ScriptValue::Boolean(*value),
+            JsonValue::Number(_) => ScriptValue::Number(json.as_f64().unwrap_or(f64::NAN)),
+            JsonValue::Short(_) | JsonValue::String(_) => ScriptValue::String(json.to_string()),
+            JsonValue::Array(values) => {
+                ScriptValue::Array(values.iter().map(|v| ScriptValue::from_json_with(v, element)).collect())
+            },
+            JsonValue::Object(_) if json["element-6066-11e4-a52e-4f735466cecf"].is_string() => {
+                ScriptValue::Element(element(json["element-6066-11e4-a52e-4f735466cecf"].to_string()))
+            },
+            JsonValue::Object(_) => {
+                ScriptValue::Object(json.entries().map(|(k, v)| (k.to_string(), ScriptValue::from_json_with(v, element))).collect())
+            }
+        }
+    }
+
+    /// Convert the value back to JSON.
+    /// Elements are converted to web element references, so the result can be passed as a script argument.
+    pub fn to_json(&self) -> JsonValue {
+        match self {
+            ScriptValue::Null => JsonValue::Null,
+            ScriptValue::Boolean(value) => (*value).into(),
+            ScriptValue::Number(value) => (*value).into(),
+            ScriptValue::String(value) => value.as_str().into(),
+            ScriptValue::Array(values) => values.iter().map(|v| v.to_json()).collect::<Vec<JsonValue>>().into(),
+            ScriptValue::Object(entries) => {
+                let mut object = object!{};
+                for (key, value) in entries {
+                    object[key.as_str()] = value.to_json();
+                }
+                object
+            },
+            ScriptValue::Element(element) => ElementRef::from(element).as_json_object()
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, ScriptValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ScriptValue::Boolean(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ScriptValue::Number(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    /// Return the number if it is an integer.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ScriptValue::Number(value) if value.fract() == 0.0 && *value >= i64::MIN as f64 && *value <= i64::MAX as f64 => Some(*value as i64),
+            _ => None
+        }
+    }
+
+    /// Return the number if it is a positive integer.
+    pub fn as_usize(&self) -> Option<usize> {
+        match self.as_i64() {
+            Some(value) if value >= 0 => Some(value as usize),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ScriptValue::String(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<ScriptValue<E>>> {
+        match self {
+            ScriptValue::Array(values) => Some(values),
+            _ => None
+        }
+    }
+
+    /// Return the value associated with a key if this is an object.
+    pub fn get(&self, key: &str) -> Option<&ScriptValue<E>> {
+        match self {
+            ScriptValue::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None
+        }
+    }
+
+    pub fn as_element(&self) -> Option<&E> {
+        match self {
+            ScriptValue::Element(element) => Some(element),
+            _ => None
+        }
+    }
+
+    pub fn into_string(self) -> Option<String> {
+        match self {
+            ScriptValue::String(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn into_array(self) -> Option<Vec<ScriptValue<E>>> {
+        match self {
+            ScriptValue::Array(values) => Some(values),
+            _ => None
+        }
+    }
+
+    pub fn into_element(self) -> Option<E> {
+        match self {
+            ScriptValue::Element(element) => Some(element),
+            _ => None
+        }
+    }
+
+    /// Return the elements if this is an array of elements (like the result of `document.querySelectorAll()`).
+    pub fn into_elements(self) -> Option<Vec<E>> {
+        self.into_array()?.into_iter().map(|v| v.into_element()).collect()
+    }
+}
@@ -0,0 +1,344 @@
+//! Driver services launch and manage a webdriver process ([geckodriver](https://github.com/mozilla/geckodriver) or [chromedriver](https://chromedriver.chromium.org/))
+
+use std::time::{Duration, Instant};
+use std::result::Result;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::net::{TcpListener, Ipv4Addr};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::fs::File;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::env;
+use crate::enums::*;
+use crate::error::*;
+use crate::http_requests::{Client, get_status};
+use log::{debug, info, warn, error};
+
+/// A webdriver process launched by this crate.
+/// The process is killed when the service is dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use lw_webdriver::{service::DriverService, session::SessionBuilder, enums::Browser};
+///
+/// let service = DriverService::start(Browser::Firefox).unwrap();
+/// let mut session = SessionBuilder::new(Browser::Firefox)
+///     .driver_service(service)
+///     .build()
+///     .unwrap();
+/// ```
+pub struct DriverService {
+    process: Child,
+    url: String,
+    port: u16,
+    output: DriverOutput,
+    readers: Vec<thread::JoinHandle<()>>
+}
+
+impl DriverService {
+    /// Launch the driver of a [browser](../enums/enum.Browser.html) with default options.
+    /// See [DriverServiceBuilder](struct.DriverServiceBuilder.html) for more options.
+    pub fn start(browser: Browser) -> Result<DriverService, WebdriverError> {
+        DriverServiceBuilder::new(browser).start()
+    }
+
+    /// Return the url the driver is listening at.
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+
+    /// Return the port the driver is listening on.
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    /// Return a handle to the last lines written by the driver on stdout and stderr.
+    pub fn get_output(&self) -> DriverOutput {
+        self.output.clone()
+    }
+
+    fn wait_ready(&mut self, timeout: Duration) -> Result<(), WebdriverError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Ok(Some(status)) = self.process.try_wait() {
+                error!("Webdriver exited before being ready ({})", status);
+                self.wait_readers(Duration::from_secs(1));
+                let error: WebdriverError = ErrorKind::FailedToLaunchDriver(format!("the driver exited ({})", status)).into();
+                return Err(error.with_driver_output(self.output.get_text()));
+            }
+            let mut client = Client::new(&self.url);
+            client.driver_output = Some(self.output.clone());
+            match client.run(get_status()) {
+                Ok(true) => return Ok(()),
+                Ok(false) => debug!("webdriver is not ready yet"),
+                Err(e) => debug!("webdriver is not reachable yet ({:?})", e)
+            }
+            if Instant::now() >= deadline {
+                error!("Webdriver was not ready after {:?}", timeout);
+                self.wait_readers(Duration::from_millis(100));
+                let error: WebdriverError = ErrorKind::DriverNotReady.into();
+                return Err(error.with_driver_output(self.output.get_text()));
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    /// Give some time to the output readers to collect the last lines
+    fn wait_readers(&self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline && !self.readers.iter().all(|reader| reader.is_finished()) {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+impl Drop for DriverService {
+    #[allow(unused_must_use)]
+    fn drop(&mut self) {
+        warn!("Killing webdriver process (may fail silently)");
+        self.process.kill();
+        self.process.wait();
+    }
+}
+
+/// Configure and launch a [DriverService](struct.DriverService.html).
+///
+/// # Example
+///
+/// ```rust
+/// use lw_webdriver::{service::DriverServiceBuilder, enums::Browser};
+/// use std::time::Duration;
+///
+/// let service = DriverServiceBuilder::new(Browser::Chrome)
+///     .binary("/usr/local/bin/chromedriver")
+///     .startup_timeout(Duration::from_secs(30))
+///     .start()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct DriverServiceBuilder {
+    browser: Browser,
+    binary: Option<PathBuf>,
+    port: Option<u16>,
+    startup_timeout: Duration,
+    args: Vec<String>,
+    output_capacity: usize,
+    log_file: Option<PathBuf>
+}
+
+impl DriverServiceBuilder {
+    /// The driver will be searched in the current directory and then in the `PATH`.
+    /// It will listen on a free port and must be ready within 10 seconds.
+    /// The last 200 lines of its output are kept.
+    pub fn new(browser: Browser) -> Self {
+        DriverServiceBuilder {
+            browser,
+            binary: None,
+            port: None,
+            startup_timeout: Duration::from_secs(10),
+            args: Vec::new(),
+            output_capacity: 200,
+            log_file: None
+        }
+    }
+
+    /// Set the path of the driver executable.
+    pub fn binary<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.binary = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set the port the driver will listen on.
+    /// By default, a free port is picked.
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Set how long to wait for the driver to be ready.
+    pub fn startup_timeout(mut self, startup_timeout: Duration) -> Self {
+        self.startup_timeout = startup_timeout;
+        self
+    }
+
+    /// Add an argument to the driver command line.
+    pub fn arg(mut self, arg: &str) -> Self {
+        self.args.push(arg.to_string());
+        self
+    }
+
+    /// Set how many lines of the driver output are kept in memory.
+    pub fn output_capacity(mut self, lines: usize) -> Self {
+        self.output_capacity = lines;
+        self
+    }
+
+    /// Write the whole driver output to a file.
+    pub fn log_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.log_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    fn find_binary(&self) -> Result<PathBuf, WebdriverError> {
+        if let Some(binary) = &self.binary {
+            if binary.is_file() {
+                return Ok(binary.clone());
+            }
+            return Err(ErrorKind::DriverNotFound.into());
+        }
+
+        let name = match self.browser {
+            Browser::Firefox => "geckodriver",
+            Browser::Chrome => "chromedriver"
+        };
+        let name = if cfg!(windows) {
+            format!("{}.exe", name)
+        } else {
+            name.to_string()
+        };
+
+        let local = Path::new(".").join(&name);
+        if local.is_file() {
+            return Ok(local);
+        }
+        if let Some(paths) = env::var_os("PATH") {
+            for directory in env::split_paths(&paths) {
+                let candidate = directory.join(&name);
+                if candidate.is_file() {
+                    return Ok(candidate);
+                }
+            }
+        }
+
+        Err(ErrorKind::DriverNotFound.into())
+    }
+
+    /// Launch the driver and wait until it is ready.
+    pub fn start(self) -> Result<DriverService, WebdriverError> {
+        let binary = self.find_binary()?;
+        let port = match self.port {
+            Some(port) => port,
+            None => free_port()?
+        };
+
+        info!("Launching {} on port {}...", binary.display(), port);
+        let mut command = Command::new(&binary);
+        match self.browser {
+            Browser::Firefox => command.arg("--port").arg(port.to_string()),
+            Browser::Chrome => command.arg(format!("--port={}", port))
+        };
+        let log_file = match &self.log_file {
+            Some(path) => Some(File::create(path).map_err(|e| {
+                error!("Failed to create log file {}. error: {}", path.display(), e);
+                WebdriverError::from(ErrorKind::FailedToLaunchDriver(e.to_string()))
+            })?),
+            None => None
+        };
+        let mut process = command
+            .args(&self.args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                error!("Failed to launch {}. error: {}", binary.display(), e);
+                WebdriverError::from(ErrorKind::FailedToLaunchDriver(e.to_string()))
+            })?;
+
+        let output = DriverOutput::new(self.output_capacity, log_file);
+        let mut readers = Vec::new();
+        if let Some(stdout) = process.stdout.take() {
+            readers.push(output.capture(stdout));
+        }
+        if let Some(stderr) = process.stderr.take() {
+            readers.push(output.capture(stderr));
+        }
+
+        let mut service = DriverService {
+            process,
+            url: format!("http://localhost:{}", port),
+            port,
+            output,
+            readers
+        };
+        service.wait_ready(self.startup_timeout)?;
+        info!("Webdriver ready at {}", service.url);
+
+        Ok(service)
+    }
+}
+
+/// The last lines written by a driver process on stdout and stderr.
+/// The handle stays valid after the [DriverService](struct.DriverService.html) is dropped.
+#[derive(Clone)]
+pub struct DriverOutput {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize,
+    log_file: Option<Arc<Mutex<File>>>
+}
+
+impl DriverOutput {
+    fn new(capacity: usize, log_file: Option<File>) -> Self {
+        DriverOutput {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+            log_file: log_file.map(|file| Arc::new(Mutex::new(file)))
+        }
+    }
+
+    /// Read a stream in a background thread until it is closed.
+    fn capture<R: Read + Send + 'static>(&self, stream: R) -> thread::JoinHandle<()> {
+        let output = self.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                match line {
+                    Ok(line) => output.push(line),
+                    Err(_) => break
+                }
+            }
+        })
+    }
+
+    #[allow(unused_must_use)]
+    fn push(&self, line: String) {
+        debug!("webdriver: {}", line);
+        if let Some(log_file) = &self.log_file {
+            if let Ok(mut log_file) = log_file.lock() {
+                writeln!(log_file, "{}", line);
+            }
+        }
+        if self.capacity == 0 {
+            return;
+        }
+        if let Ok(mut lines) = self.lines.lock() {
+            if lines.len() >= self.capacity {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        }
+    }
+
+    /// Return the kept lines.
+    pub fn get_lines(&self) -> Vec<String> {
+        match self.lines.lock() {
+            Ok(lines) => lines.iter().cloned().collect(),
+            Err(_) => Vec::new()
+        }
+    }
+
+    /// Return the kept lines joined by line breaks.
+    pub fn get_text(&self) -> String {
+        self.get_lines().join("\n")
+    }
+}
+
+/// Ask the system for a port nobody is listening on.
+fn free_port() -> Result<u16, WebdriverError> {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .and_then(|listener| listener.local_addr())
+        .map(|address| address.port())
+        .map_err(|e| ErrorKind::FailedToLaunchDriver(e.to_string()).into())
+}
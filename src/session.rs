@@ -1,21 +1,30 @@
 //! Sessions allow you to control tabs
 
 use json::*;
-use std::time::Duration;
 use std::result::Result;
 use crate::enums::*;
 use crate::timeouts::*;
 use crate::tab::*;
+use crate::capabilities::*;
 use crate::error::*;
-use std::process::{Command, Stdio};
-use std::thread;
-use log::{debug, info, warn, error};
-use std::rc::Rc;
+use crate::service::*;
+use crate::transport::Transport;
+#[cfg(feature = "async")]
+use crate::asynchronous::{AsyncSession, AsyncTransport, AsyncClient};
+use log::{info, warn, error};
+use std::sync::Arc;
 use crate::http_requests::*;
 
+/// The url used by [Session::new()](struct.Session.html#method.new).
+pub const DEFAULT_WEBDRIVER_URL: &str = "http://localhost:4444";
+
 /// This is the more important object.
 /// Tabs can be accessed within the session.
 /// 
+/// Sessions, tabs and elements are `Send` and `Sync`, so they can be moved to or shared with other threads.
+/// Commands of different tabs are serialized internally, so that a command is never sent to the wrong tab.
+/// Selecting another tab resets the frame of a tab to the top-level document, so use [Tab::frame()](../tab/struct.Tab.html#method.frame) rather than [Tab::switch_to_frame()](../tab/struct.Tab.html#method.switch_to_frame) when other threads use other tabs.
+/// 
 /// # Example
 /// 
 /// ```rust
@@ -31,19 +40,22 @@ use crate::http_requests::*;
 /// session.tabs[1].navigate("https://mubelotix.dev/").unwrap();
 /// ```
 pub struct Session {
-    id: Rc<String>,
+    pub(crate) id: Arc<String>,
+    pub(crate) client: Arc<Client>,
     /// Contains every manually created tabs and default tab.
     /// Do not contains tabs created by web pages with javascript unless you call [update_tabs()](https://to.do/).
     pub tabs: Vec<Tab>,
-    webdriver_process: Option<std::process::Child>,
+    webdriver_process: Option<DriverService>,
+    keep_open: bool,
+    ended: bool
 }
 
 impl Session {
     /// Create a session of a specific [browser](https://to.do/).
     /// Headless mean that the browser will be opened but not displayed (useful for servers).
-    /// The crate will request a webdriver server at http://localhost:4444.
-    /// If no webdriver is listening, one will be launched, but the program ([geckodriver](https://to.do/) or [chromedriver](https://to.do/))
-    /// must be located at the same place than the running program.
+    /// The crate will request a webdriver server at [DEFAULT_WEBDRIVER_URL](constant.DEFAULT_WEBDRIVER_URL.html).
+    /// If no webdriver is listening, one will be launched with a [DriverService](../service/struct.DriverService.html).
+    /// The program ([geckodriver](https://to.do/) or [chromedriver](https://to.do/)) must be located in the current directory or in the `PATH`.
     /// 
     /// # Example
     /// 
@@ -53,122 +65,58 @@ impl Session {
     /// ```
     pub fn new(browser: Browser, headless: bool) -> Result<Self, WebdriverError> {
         info!{"Creating a session..."};
-        let result = Session::new_session(browser, headless);
-
-        if let Err(WebdriverError::FailedRequest) = result {
-            warn!{"No webdriver launched."}
-            if cfg!(unix) {
-                if browser == Browser::Firefox {
-                    info!{"Launching geckodriver..."}
-                    let p = Command::new("./geckodriver")
-                        .stdout(Stdio::null())
-                        .stderr(Stdio::null())
-                        .spawn()
-                        .expect("Failed to start process.");
-                    thread::sleep(Duration::from_millis(2000));
-                    let result = Session::new_session(browser, headless);
-                    if let Ok(mut result) = result {
-                        info!{"Session created successfully."}
-                        result.webdriver_process = Some(p);
-                        return Ok(result);
-                    } else if let Err(e) = result {
-                        error!("Failed to create session. error : {:?}.", e);
-                        return Err(e);
-                    }
-                } else {
-                    info!{"Launching chromedriver..."}
-                    let p = Command::new("./chromedriver")
-                        .arg("--port=4444")
-                        .stdout(Stdio::null())
-                        .stderr(Stdio::null())
-                        .spawn()
-                        .expect("Failed to start process");
-                    thread::sleep(Duration::from_millis(2000));
-                    let result = Session::new_session(browser, headless);
-                    if let Ok(mut result) = result {
-                        info!{"Session created successfully."}
-                        result.webdriver_process = Some(p);
-                        return Ok(result);
-                    } else if let Err(e) = result{
-                        error!("Failed to create session. error : {:?}.", e);
-                        return Err(e);
-                    }
+        match Session::new_session(browser, headless) {
+            Err(error) if error == ErrorKind::FailedRequest => {
+                warn!{"No webdriver launched."}
+                let service = DriverService::start(browser)?;
+                let result = SessionBuilder::new(browser)
+                    .headless(headless)
+                    .driver_service(service)
+                    .build();
+                match &result {
+                    Ok(_) => info!{"Session created successfully."},
+                    Err(e) => error!("Failed to create session. error : {:?}.", e)
                 }
-            } else {
-                panic!("Please launch the webdriver manually.")
-            }
-        } else {
-            return result;
+                result
+            },
+            result => result
         }
-        
-        result
     }
 
-    fn new_session(browser: Browser, headless: bool)  -> Result<Self, WebdriverError> {
-        // Detect platform
-        let platform = Platform::current();
-        if let Platform::Unknow = platform {
-            return Err(WebdriverError::UnsupportedPlatform);
-        }
+    /// Create a session of a specific [browser](https://to.do/) on a webdriver server listening at `webdriver_url`.
+    /// This allows using a driver on another port, on another host or behind a path prefix (like `http://localhost:4444/wd/hub` for Selenium Grid).
+    /// Unlike [new()](#method.new), no webdriver will be launched if none is listening.
+    /// Use a [SessionBuilder](struct.SessionBuilder.html) for more options.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use lw_webdriver::{session::Session, enums::Browser};
+    /// let mut session = Session::new_with_url("http://localhost:4444", Browser::Firefox, false).unwrap();
+    /// ```
+    pub fn new_with_url(webdriver_url: &str, browser: Browser, headless: bool) -> Result<Self, WebdriverError> {
+        SessionBuilder::new(browser)
+            .webdriver_url(webdriver_url)
+            .headless(headless)
+            .build()
+    }
 
-        // Generate capabilities
-        let post_data = match browser {
-            Browser::Firefox => {
-                if headless {
-                    object!{
-                        "capabilities" => object!{
-                            "alwaysMatch" => object!{
-                                "platformName" => platform.to_string(),
-                                "browserName" => browser.to_string(),
-                                "moz:firefoxOptions" => object! {
-                                    "args" => array!{"-headless"}
-                                },
-                            }
-                        }
-                    }
-                } else {
-                    object!{
-                        "capabilities" => object!{
-                            "alwaysMatch" => object!{
-                                "platformName" => platform.to_string(),
-                                "browserName" => browser.to_string()
-                            }
-                        }
-                    }
-                }
-            },
-            Browser::Chrome => {
-                if headless {
-                    object!{
-                        "capabilities" => object!{
-                            "alwaysMatch" => object!{
-                                "platformName" => platform.to_string(),
-                                "browserName" => browser.to_string(),
-                                "goog:chromeOptions" => object! {
-                                    "args" => array!{"-headless"}
-                                }
-                            }
-                        }
-                    }
-                } else {
-                    object!{
-                        "capabilities" => object!{
-                            "alwaysMatch" => object!{
-                                "platformName" => platform.to_string(),
-                                "browserName" => browser.to_string()
-                            }
-                        }
-                    }
-                }
-            }
-        };
-        
+    fn new_session(browser: Browser, headless: bool) -> Result<Self, WebdriverError> {
+        SessionBuilder::new(browser)
+            .headless(headless)
+            .build()
+    }
+
+    pub(crate) fn new_from_capabilities(client: Client, capabilities: &JsonValue) -> Result<Self, WebdriverError> {
         // Send request
-        let session_id = new_session(&post_data.to_string())?;
+        let session_id = client.run(new_session(&capabilities.to_string()))?;
         let mut session = Session {
-            id: Rc::new(session_id),
+            id: Arc::new(session_id),
+            client: Arc::new(client),
             tabs: Vec::new(),
-            webdriver_process: None
+            webdriver_process: None,
+            keep_open: false,
+            ended: false
         };
 
         session.update_tabs()?;
@@ -190,8 +138,8 @@ impl Session {
     /// assert_eq!(session.tabs.len(), 2); // new tab is accessible
     /// ```
     pub fn open_tab(&mut self) -> Result<usize, WebdriverError> {
-        let tab_id = new_tab(&self.id)?;
-        let new_tab = Tab::new_from(tab_id, Rc::clone(&self.id));
+        let tab_id = self.client.run(new_tab(&self.id))?;
+        let new_tab = Tab::new_from(tab_id, self);
         self.tabs.push(new_tab);
 
         Ok(self.tabs.len() - 1)
@@ -231,24 +179,61 @@ impl Session {
     /// assert_eq!(session.tabs.len(), 2);
     /// ```
     pub fn update_tabs(&mut self) -> Result<(), WebdriverError> {
-        let tabs_id = get_open_tabs(&self.id)?;
+        let tabs_id = self.client.run(get_open_tabs(&self.id))?;
         for tab_id in tabs_id {
             if self.tabs.iter().position(|element| *element.id == tab_id).is_none() {
-                self.tabs.push(Tab::new_from(tab_id, Rc::clone(&self.id)));
+                let tab = Tab::new_from(tab_id, self);
+                self.tabs.push(tab);
             }
         }
 
         Ok(())
     }
 
+    /// Return the url of the webdriver server this session is running on.
+    pub fn get_webdriver_url(&self) -> &str {
+        &self.client.url
+    }
+
+    /// Choose whether the browser stays open when the session is dropped.
+    /// By default, the session is ended and the browser is closed.
+    /// If the webdriver was launched by this crate, it is left running too.
+    pub fn set_keep_open(&mut self, keep_open: bool) {
+        self.keep_open = keep_open;
+    }
+
+    /// End the session and close the browser.
+    /// Unlike dropping the session, this reports whether the webdriver succeeded.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use lw_webdriver::{session::Session, enums::Browser};
+    /// let session = Session::new(Browser::Firefox, false).unwrap();
+    /// session.quit().unwrap();
+    /// ```
+    pub fn quit(mut self) -> Result<(), WebdriverError> {
+        self.end()
+    }
+
+    /// Send DELETE /session, which closes every tab
+    fn end(&mut self) -> Result<(), WebdriverError> {
+        for tab in self.tabs.iter_mut() {
+            tab.close_on_drop = false;
+        }
+        self.tabs.clear();
+        self.ended = true;
+        self.client.run(delete_session(&self.id))
+    }
+
     /// This is a simple method getting [timeouts](https://to.do/) of the session.
     pub fn get_timeouts(&self) -> Result<Timeouts, WebdriverError> {
-        Ok(get_timeouts(&self.id)?)
+        self.client.run(get_timeouts(&self.id))
     }
 
     /// This is a simple method setting [timeouts](https://to.do/) of the session.
     pub fn set_timeouts(&mut self, timeouts: Timeouts) -> Result<(), WebdriverError> {
-        Ok(set_timeouts(&self.id, timeouts)?)
+        self.client.run(set_timeouts(&self.id, timeouts))
     }
 }
 
@@ -267,10 +252,227 @@ impl WebdriverObject for Session {
 impl Drop for Session {
     #[allow(unused_must_use)]
     fn drop(&mut self) {
-        self.tabs.clear();
-        if self.webdriver_process.is_some() {
-            warn!("Killing webdriver process (may fail silently)");
-            self.webdriver_process.take().unwrap().kill();
+        if self.keep_open {
+            for tab in self.tabs.iter_mut() {
+                tab.close_on_drop = false;
+            }
+            self.tabs.clear();
+            if let Some(webdriver_process) = self.webdriver_process.take() {
+                info!("Leaving the webdriver process running");
+                std::mem::forget(webdriver_process);
+            }
+            return;
+        }
+        if !self.ended {
+            self.end();
+        }
+        self.webdriver_process.take();
+    }
+}
+
+/// Create a [Session](struct.Session.html) with custom [capabilities](../capabilities/struct.Capabilities.html).
+/// 
+/// Capabilities in `always_match` are required, and the webdriver will use the first set of `first_match` capabilities it can satisfy.
+/// Unlike [Session::new()](struct.Session.html#method.new), no webdriver will be launched if none is listening.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use lw_webdriver::{session::SessionBuilder, capabilities::{Capabilities, PageLoadStrategy}, enums::Browser};
+/// 
+/// let mut capabilities = Capabilities::new();
+/// capabilities.accept_insecure_certs = Some(true);
+/// capabilities.page_load_strategy = Some(PageLoadStrategy::Eager);
+/// 
+/// let mut session = SessionBuilder::new(Browser::Firefox)
+///     .webdriver_url("http://localhost:4444")
+///     .headless(true)
+///     .always_match(capabilities)
+///     .build()
+///     .unwrap();
+/// ```
+pub struct SessionBuilder {
+    browser: Browser,
+    webdriver_url: String,
+    headless: bool,
+    always_match: Capabilities,
+    first_match: Vec<Capabilities>,
+    driver_service: Option<DriverService>,
+    transport: Option<Box<dyn Transport>>,
+    #[cfg(feature = "async")]
+    async_transport: Option<Box<dyn AsyncTransport>>,
+    keep_open: bool
+}
+
+impl SessionBuilder {
+    /// Start building a session of a specific [browser](https://to.do/) on [DEFAULT_WEBDRIVER_URL](constant.DEFAULT_WEBDRIVER_URL.html).
+    pub fn new(browser: Browser) -> Self {
+        SessionBuilder {
+            browser,
+            webdriver_url: DEFAULT_WEBDRIVER_URL.to_string(),
+            headless: false,
+            always_match: Capabilities::new(),
+            first_match: Vec::new(),
+            driver_service: None,
+            transport: None,
+            #[cfg(feature = "async")]
+            async_transport: None,
+            keep_open: false
+        }
+    }
+
+    /// Set the url of the webdriver server.
+    pub fn webdriver_url(mut self, webdriver_url: &str) -> Self {
+        self.webdriver_url = webdriver_url.to_string();
+        self
+    }
+
+    /// Use a webdriver launched by a [DriverService](../service/struct.DriverService.html).
+    /// This overrides the webdriver url, and the driver process will be killed when the session is dropped.
+    pub fn driver_service(mut self, driver_service: DriverService) -> Self {
+        self.webdriver_url = driver_service.get_url().to_string();
+        self.driver_service = Some(driver_service);
+        self
+    }
+
+    /// Send the requests of the session with another [Transport](../transport/trait.Transport.html) than minreq.
+    pub fn transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Box::new(transport));
+        self
+    }
+
+    /// Send the requests of an [async session](#method.build_async) with another [AsyncTransport](../asynchronous/trait.AsyncTransport.html) than reqwest.
+    #[cfg(feature = "async")]
+    pub fn async_transport<T: AsyncTransport + 'static>(mut self, transport: T) -> Self {
+        self.async_transport = Some(Box::new(transport));
+        self
+    }
+
+    /// Open the browser without displaying it (useful for servers).
+    /// The argument is added to the browser options of firstMatch when they are set there, and to alwaysMatch otherwise.
+    pub fn headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+
+    /// Leave the browser open when the session is dropped.
+    /// See [Session::set_keep_open()](struct.Session.html#method.set_keep_open).
+    pub fn keep_open(mut self, keep_open: bool) -> Self {
+        self.keep_open = keep_open;
+        self
+    }
+
+    /// Set the capabilities that the session must satisfy.
+    /// The browser name and the platform name are filled automatically if they are not set (here or in [first_match()](#method.first_match)).
+    pub fn always_match(mut self, capabilities: Capabilities) -> Self {
+        self.always_match = capabilities;
+        self
+    }
+
+    /// Add a set of alternative capabilities.
+    /// The webdriver will use the first one it can satisfy.
+    pub fn first_match(mut self, capabilities: Capabilities) -> Self {
+        self.first_match.push(capabilities);
+        self
+    }
+
+    /// Generate the body of the session creation request.
+    pub fn to_json(&self) -> Result<JsonValue, WebdriverError> {
+        // a key can't be in both alwaysMatch and firstMatch, so keys set by firstMatch are not filled
+        let mut always_match = self.always_match.clone();
+        if always_match.browser_name.is_none() && self.first_match.iter().all(|c| c.browser_name.is_none()) {
+            always_match.browser_name = Some(self.browser);
+        }
+        if always_match.platform_name.is_none() && self.first_match.iter().all(|c| c.platform_name.is_none()) {
+            let platform = Platform::current();
+            if let Platform::Unknow = platform {
+                return Err(ErrorKind::UnsupportedPlatform.into());
+            }
+            always_match.platform_name = Some(platform.to_string().to_string());
         }
+        let mut first_match = self.first_match.clone();
+        if self.headless {
+            // the options go where the browser options are already set, so that they are not in both alwaysMatch and firstMatch
+            let default_browser = always_match.browser_name.unwrap_or(self.browser);
+            let browsers: Vec<Browser> = if first_match.is_empty() {
+                vec![default_browser]
+            } else {
+                first_match.iter().map(|c| c.browser_name.unwrap_or(default_browser)).collect()
+            };
+            for key in ["moz:firefoxOptions", "goog:chromeOptions"].iter() {
+                if !browsers.iter().any(|browser| options_key(*browser) == *key) {
+                    continue;
+                }
+                if first_match.iter().any(|c| c.get_extension(key).is_some()) {
+                    for (capabilities, browser) in first_match.iter_mut().zip(browsers.iter()) {
+                        if options_key(*browser) == *key {
+                            add_headless_arg(capabilities.get_extension_mut(key))?;
+                        }
+                    }
+                } else {
+                    add_headless_arg(always_match.get_extension_mut(key))?;
+                }
+            }
+        }
+
+        let mut capabilities = object!{
+            "alwaysMatch" => always_match.to_json()
+        };
+        if !first_match.is_empty() {
+            capabilities["firstMatch"] = first_match.iter().map(|c| c.to_json()).collect::<Vec<JsonValue>>().into();
+        }
+
+        Ok(object!{
+            "capabilities" => capabilities
+        })
     }
-}
\ No newline at end of file
+
+    /// Create the session.
+    pub fn build(mut self) -> Result<Session, WebdriverError> {
+        info!{"Creating a session on {}...", self.webdriver_url};
+        let capabilities = self.to_json()?;
+        let mut client = Client::new(&self.webdriver_url);
+        client.driver_output = self.driver_service.as_ref().map(|s| s.get_output());
+        if let Some(transport) = self.transport.take() {
+            client.transport = transport;
+        }
+        let mut session = Session::new_from_capabilities(client, &capabilities)?;
+        session.webdriver_process = self.driver_service;
+        session.keep_open = self.keep_open;
+        Ok(session)
+    }
+
+    /// Create an [async session](../asynchronous/struct.AsyncSession.html) (requires the `async` feature).
+    /// Its requests are sent with the [async transport](#method.async_transport), and the blocking [transport](#method.transport) is ignored.
+    #[cfg(feature = "async")]
+    pub fn build_async(mut self) -> impl std::future::Future<Output = Result<AsyncSession, WebdriverError>> + Send {
+        info!{"Creating an async session on {}...", self.webdriver_url};
+        let capabilities = self.to_json();
+        let mut client = AsyncClient::new(&self.webdriver_url);
+        client.driver_output = self.driver_service.as_ref().map(|s| s.get_output());
+        if let Some(transport) = self.async_transport.take() {
+            client.transport = transport;
+        }
+        let driver_service = self.driver_service.take();
+        let keep_open = self.keep_open;
+
+        async move {
+            AsyncSession::new_from_capabilities(client, &capabilities?, driver_service, keep_open).await
+        }
+    }
+}
+
+/// The capability containing the options of a browser
+fn options_key(browser: Browser) -> &'static str {
+    match browser {
+        Browser::Firefox => "moz:firefoxOptions",
+        Browser::Chrome => "goog:chromeOptions"
+    }
+}
+
+fn add_headless_arg(options: &mut JsonValue) -> Result<(), WebdriverError> {
+    if !options["args"].is_array() {
+        options["args"] = array!{};
+    }
+    options["args"].push("-headless").map_err(|_| WebdriverError::from(ErrorKind::InvalidArgument))
+}
@@ -2,15 +2,24 @@
 
 use json::*;
 use std::result::Result;
-use crate::elements::*;
 use crate::session::*;
 use crate::enums::*;
 use crate::error::*;
-use log::{info, error};
 use crate::elements::Element;
-use std::rc::Rc;
-use crate::http_requests::{get_selected_tab, select_tab, navigate, close_active_tab, find_element,
-    get_active_tab_url, get_active_tab_title, back, forward, refresh, execute_script_sync, get_all_cookies, set_cookie, get_page_source};
+use crate::script::ScriptValue;
+use crate::cookies::Cookie;
+use crate::screenshot::Screenshot;
+use crate::print::PrintOptions;
+use crate::actions::Actions;
+use crate::alerts::Alert;
+use crate::frames::{FrameId, Frame};
+use crate::window::WindowRect;
+use std::sync::Arc;
+use crate::http_requests::{Client, Command, navigate, close_active_tab, find_element, find_elements,
+    get_active_tab_url, get_active_tab_title, back, forward, refresh, execute_script_sync, execute_script_async, get_all_cookies, get_named_cookie, set_cookie,
+    delete_cookie, delete_all_cookies, get_page_source, take_screenshot, print_page,
+    perform_actions, release_actions, get_alert_text, switch_to_frame, switch_to_parent_frame,
+    get_window_rect, set_window_rect, resize_window};
 
 /// Tabs are used to load a site and get informations.
 /// 
@@ -23,20 +32,27 @@ use crate::http_requests::{get_selected_tab, select_tab, navigate, close_active_
 /// session.tabs[0].navigate("https://www.mozilla.org/fr/").unwrap();
 /// ```
 pub struct Tab {
-    pub(crate) id: Rc<String>,
-    pub(crate) session_id: Rc<String>
+    pub(crate) id: Arc<String>,
+    pub(crate) session_id: Arc<String>,
+    pub(crate) client: Arc<Client>,
+    /// Set to false when the whole session is ended, so there is no need to close tabs one by one.
+    pub(crate) close_on_drop: bool
 }
 
 impl Tab {
-    pub fn new_from(id: String, session_id: Rc<String>) -> Tab {
+    /// Create a tab from the handle of a window of a session.
+    /// The tab is not added to [session.tabs](../session/struct.Session.html#structfield.tabs).
+    pub fn new_from(id: String, session: &Session) -> Tab {
         Tab {
-            id: Rc::new(id),
-            session_id
+            id: Arc::new(id),
+            session_id: Arc::clone(&session.id),
+            client: Arc::clone(&session.client),
+            close_on_drop: true
         }
     }
 
-    pub fn get_session_id(&self) -> Rc<String> {
-        Rc::clone(&self.session_id)
+    pub fn get_session_id(&self) -> Arc<String> {
+        Arc::clone(&self.session_id)
     }
 
     /// Create a new tab in a session.
@@ -49,95 +65,273 @@ impl Tab {
     /// Select this tab.
     /// Selection is done automatically by this crate when you get informations.
     pub fn select(&self) -> Result<(), WebdriverError> {
-        // check if it is needed to select the tab
-        if let Ok(id) = get_selected_tab(&self.session_id) {
-            if id == *self.id {
-                return Ok(());
-            }
-        }
+        self.client.select(&self.session_id, &self.id)
+    }
 
-        // select tab
-        select_tab(&self.session_id, &self.id)
+    /// Select this tab and send a command to it, without letting other threads select another tab in between
+    pub(crate) fn run<T>(&self, command: Command<T>) -> Result<T, WebdriverError> {
+        self.client.run_in_tab(&self.session_id, &self.id, command)
     }
 
     /// Load a website
-    pub fn navigate(&mut self, url: &str) -> Result<(), WebdriverError> {
-        self.select()?;
-        navigate(&self.session_id, url)
+    pub fn navigate(&self, url: &str) -> Result<(), WebdriverError> {
+        self.run(navigate(&self.session_id, url))
+    }
+
+    /// Run the next searches and scripts of this tab inside a frame of the current document (or the top-level document).
+    /// Prefer [frame()](#method.frame) which switches back automatically.
+    ///
+    /// Selecting another tab (for example from another thread) resets the frame of this tab to the top-level document.
+    pub fn switch_to_frame(&self, frame: FrameId) -> Result<(), WebdriverError> {
+        self.run(switch_to_frame(&self.session_id, &frame))
+    }
+
+    /// Leave the current frame for its parent.
+    pub fn switch_to_parent_frame(&self) -> Result<(), WebdriverError> {
+        self.run(switch_to_parent_frame(&self.session_id))
+    }
+
+    /// Switch to a frame until the returned [Frame](../frames/struct.Frame.html) is dropped.
+    /// Return a NoSuchFrame error if the frame does not exist.
+    ///
+    /// The frame is entered again if another thread selects another tab in the meantime.
+    pub fn frame(&mut self, frame: FrameId) -> Result<Frame<'_>, WebdriverError> {
+        self.client.enter_frame(&self.session_id, &self.id, &frame)?;
+        Ok(Frame::new(self))
     }
 
     /// Find an element in the tab, selected by a [Selector](../enums/enum.Selector.html).
-    pub fn find(&mut self, selector: Selector, tofind: &str) -> Result<Option<Element>, WebdriverError> {
-        self.select()?;
-        match find_element(&self.session_id, selector, &tofind) {
+    pub fn find(&self, selector: Selector, tofind: &str) -> Result<Option<Element>, WebdriverError> {
+        match self.run(find_element(&self.session_id, selector, tofind)) {
             Ok(id) => {
-                Ok(Some(Element::new(id, Rc::clone(&self.session_id), Rc::clone(&self.id))))
+                Ok(Some(Element::new(id, Arc::clone(&self.session_id), Arc::clone(&self.id), Arc::clone(&self.client))))
             },
-            Err(error) if error == WebdriverError::NoSuchElement => {
+            Err(error) if error == ErrorKind::NoSuchElement => {
                 Ok(None)
             },
             Err(error) => {
-                return Err(error)
+                Err(error)
             }
         }
     }
 
+    /// Find every element in the tab matching a [Selector](../enums/enum.Selector.html).
+    /// Elements are returned in document order, and the result is empty if nothing matches.
+    pub fn find_all(&self, selector: Selector, tofind: &str) -> Result<Vec<Element>, WebdriverError> {
+        let ids = self.run(find_elements(&self.session_id, selector, tofind))?;
+        Ok(ids.into_iter().map(|id| Element::new(id, Arc::clone(&self.session_id), Arc::clone(&self.id), Arc::clone(&self.client))).collect())
+    }
+
     /// Return the url of the current web page.
     pub fn get_url(&self) -> Result<String, WebdriverError> {
-        self.select()?;
-        get_active_tab_url(&self.session_id)
+        self.run(get_active_tab_url(&self.session_id))
     }
 
     /// Return the title of the tab.
     pub fn get_title(&self) -> Result<String, WebdriverError> {
-        self.select()?;
-        get_active_tab_title(&self.session_id)
+        self.run(get_active_tab_title(&self.session_id))
     }
 
     /// Navigate to the previous page.
-    pub fn back(&mut self) -> Result<(), WebdriverError> {
-        self.select()?;
-        back(&self.session_id)
+    pub fn back(&self) -> Result<(), WebdriverError> {
+        self.run(back(&self.session_id))
     }
 
     /// Navigate forward.
-    pub fn forward(&mut self) -> Result<(), WebdriverError> {
-        self.select()?;
-        forward(&self.session_id)
+    pub fn forward(&self) -> Result<(), WebdriverError> {
+        self.run(forward(&self.session_id))
     }
 
     /// Refresh the page.
-    pub fn refresh(&mut self) -> Result<(), WebdriverError> {
-        self.select()?;
-        refresh(&self.session_id)
+    pub fn refresh(&self) -> Result<(), WebdriverError> {
+        self.run(refresh(&self.session_id))
+    }
+
+    /// Execute a script in the tab and return the value it returns.
+    /// The script is the body of a function, so use `return` to get a value back.
+    /// Arguments are accessible with `arguments[i]`, and [elements](../elements/struct.Element.html) can be passed with [as_json_object()](../elements/struct.Element.html#method.as_json_object).
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use lw_webdriver::{session::Session, enums::Browser};
+    /// let mut session = Session::new(Browser::Firefox, false).unwrap();
+    /// 
+    /// let sum = session.tabs[0].execute_script("return arguments[0] + arguments[1];", vec![1.into(), 2.into()]).unwrap();
+    /// assert_eq!(sum.as_i64(), Some(3));
+    /// ```
+    pub fn execute_script(&self, script: &str, args: Vec<JsonValue>) -> Result<ScriptValue, WebdriverError> {
+        let value = self.run(execute_script_sync(&self.session_id, script, args))?;
+        Ok(ScriptValue::from_json(&value, &self.session_id, &self.id, &self.client))
     }
 
-    pub fn execute_script(&self, script: &str, args: Vec<JsonValue>) -> Result<(), WebdriverError> {
-        self.select()?;
-        execute_script_sync(&self.session_id, script, args)
+    /// Execute an asynchronous script in the tab and return the value it completes with.
+    /// A callback is passed as the last argument (`arguments[arguments.length - 1]`), and the script completes when it is called.
+    /// If the callback is not called within the script timeout ([Timeouts::script](../timeouts/struct.Timeouts.html#structfield.script)),
+    /// a [ScriptTimeoutError](../error/enum.ErrorKind.html#variant.ScriptTimeoutError) is returned.
+    /// If the script timeout is `None`, the webdriver waits forever.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use lw_webdriver::{session::Session, enums::Browser};
+    /// let mut session = Session::new(Browser::Firefox, false).unwrap();
+    /// 
+    /// // wait for a promise
+    /// let value = session.tabs[0].execute_async_script(r#"
+    ///     let done = arguments[arguments.length - 1];
+    ///     new Promise(resolve => setTimeout(() => resolve(arguments[0] * 2), 100)).then(done);
+    /// "#, vec![21.into()]).unwrap();
+    /// assert_eq!(value.as_i64(), Some(42));
+    /// ```
+    pub fn execute_async_script(&self, script: &str, args: Vec<JsonValue>) -> Result<ScriptValue, WebdriverError> {
+        let value = self.run(execute_script_async(&self.session_id, script, args))?;
+        Ok(ScriptValue::from_json(&value, &self.session_id, &self.id, &self.client))
     }
 
-    pub fn get_cookies(&self) -> Result<Vec<(String, usize, bool, String, String, bool, String)>, WebdriverError> {
-        self.select()?;
-        get_all_cookies(&self.session_id)
+    /// Return every cookie visible by the current page, including session cookies.
+    pub fn get_cookies(&self) -> Result<Vec<Cookie>, WebdriverError> {
+        self.run(get_all_cookies(&self.session_id))
     }
 
-    pub fn set_cookie(&self, cookie: (String, usize, bool, String, String, bool, String)) -> Result<(), WebdriverError> {
-        self.select()?;
-        set_cookie(&self.session_id, cookie)
+    /// Return the cookie with this name, or `None` if there is no such cookie.
+    pub fn get_cookie(&self, name: &str) -> Result<Option<Cookie>, WebdriverError> {
+        match self.run(get_named_cookie(&self.session_id, name)) {
+            Ok(cookie) => {
+                Ok(Some(cookie))
+            },
+            Err(error) if error == ErrorKind::NoSuchCookie => {
+                Ok(None)
+            },
+            Err(error) => {
+                Err(error)
+            }
+        }
     }
 
-    pub fn set_cookies(&self, cookies: Vec<(String, usize, bool, String, String, bool, String)>) -> Result<(), WebdriverError> {
-        self.select()?;
+    /// Add a cookie to the current page.
+    /// The domain of the cookie must match the domain of the page.
+    pub fn set_cookie(&self, cookie: &Cookie) -> Result<(), WebdriverError> {
+        self.run(set_cookie(&self.session_id, cookie))
+    }
+
+    pub fn set_cookies(&self, cookies: &[Cookie]) -> Result<(), WebdriverError> {
         for cookie in cookies {
-            set_cookie(&self.session_id, cookie)?
+            self.run(set_cookie(&self.session_id, cookie))?
         }
         Ok(())
     }
 
+    /// Delete the cookie with this name.
+    /// Nothing happens if there is no such cookie.
+    pub fn delete_cookie(&self, name: &str) -> Result<(), WebdriverError> {
+        self.run(delete_cookie(&self.session_id, name))
+    }
+
+    /// Delete every cookie visible by the current page.
+    pub fn delete_all_cookies(&self) -> Result<(), WebdriverError> {
+        self.run(delete_all_cookies(&self.session_id))
+    }
+
     pub fn get_page_source(&self) -> Result<String, WebdriverError> {
-        self.select()?;
-        get_page_source(&self.session_id)
+        self.run(get_page_source(&self.session_id))
+    }
+
+    /// Perform low level inputs (see [Actions](../actions/struct.Actions.html)).
+    /// Keys and buttons which are pressed and not released stay pressed until [release_actions()](#method.release_actions) is called.
+    pub fn perform_actions(&self, actions: &Actions) -> Result<(), WebdriverError> {
+        self.run(perform_actions(&self.session_id, actions))
+    }
+
+    /// Release every key and button pressed by [perform_actions()](#method.perform_actions).
+    pub fn release_actions(&self) -> Result<(), WebdriverError> {
+        self.run(release_actions(&self.session_id))
+    }
+
+    /// Return the dialog (`alert()`, `confirm()` or `prompt()`) opened in this tab, if any.
+    pub fn alert(&self) -> Result<Option<Alert<'_>>, WebdriverError> {
+        match self.run(get_alert_text(&self.session_id)) {
+            Ok(_) => Ok(Some(Alert::new(self))),
+            Err(error) if error == ErrorKind::NoSuchAlert => Ok(None),
+            Err(error) => Err(error)
+        }
+    }
+
+    /// Return the position and the size of the window containing this tab.
+    pub fn get_window_rect(&self) -> Result<WindowRect, WebdriverError> {
+        self.run(get_window_rect(&self.session_id))
+    }
+
+    /// Move and resize the window containing this tab.
+    /// The window manager may not respect the rect exactly, so the resulting rect is returned.
+    pub fn set_window_rect(&self, rect: &WindowRect) -> Result<WindowRect, WebdriverError> {
+        self.run(set_window_rect(&self.session_id, &rect.to_json()))
+    }
+
+    /// Resize the window without moving it.
+    pub fn set_window_size(&self, width: u32, height: u32) -> Result<WindowRect, WebdriverError> {
+        self.run(set_window_rect(&self.session_id, &object!{
+            "width" => width,
+            "height" => height
+        }))
+    }
+
+    /// Resize the window so that the page area (`window.innerWidth` and `window.innerHeight`) has this size.
+    /// This is useful to get the same layout and screenshots on every run, whatever the size of the browser toolbars.
+    pub fn set_viewport_size(&self, width: u32, height: u32) -> Result<WindowRect, WebdriverError> {
+        let window = self.get_window_rect()?;
+        let viewport = self.execute_script("return [window.innerWidth, window.innerHeight];", vec![])?;
+        let (inner_width, inner_height) = match viewport.as_array().map(|v| v.as_slice()) {
+            Some([width, height]) => match (width.as_i64(), height.as_i64()) {
+                (Some(width), Some(height)) => (width, height),
+                _ => return Err(ErrorKind::InvalidResponse.into())
+            },
+            _ => return Err(ErrorKind::InvalidResponse.into())
+        };
+        let border_width = (window.width as i64 - inner_width).max(0) as u32;
+        let border_height = (window.height as i64 - inner_height).max(0) as u32;
+        self.run(set_window_rect(&self.session_id, &object!{
+            "width" => width + border_width,
+            "height" => height + border_height
+        }))
+    }
+
+    /// Maximize the window containing this tab.
+    pub fn maximize(&self) -> Result<WindowRect, WebdriverError> {
+        self.run(resize_window(&self.session_id, "maximize"))
+    }
+
+    /// Minimize (iconify) the window containing this tab.
+    pub fn minimize(&self) -> Result<WindowRect, WebdriverError> {
+        self.run(resize_window(&self.session_id, "minimize"))
+    }
+
+    /// Make the window containing this tab fullscreen, like the F11 key.
+    pub fn fullscreen(&self) -> Result<WindowRect, WebdriverError> {
+        self.run(resize_window(&self.session_id, "fullscreen"))
+    }
+
+    /// Take a screenshot of the visible part of the page.
+    pub fn screenshot(&self) -> Result<Screenshot, WebdriverError> {
+        Ok(Screenshot::new(self.run(take_screenshot(&self.session_id))?))
+    }
+
+    /// Print the page to PDF and return the content of the PDF file.
+    /// Most browsers only support this in headless mode.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use lw_webdriver::{session::Session, enums::Browser, print::PrintOptions};
+    /// let mut session = Session::new(Browser::Firefox, true).unwrap();
+    /// session.tabs[0].navigate("http://example.com/").unwrap();
+    /// 
+    /// let pdf = session.tabs[0].print_pdf(&PrintOptions::default()).unwrap();
+    /// std::fs::write("example.pdf", pdf).unwrap();
+    /// # std::fs::remove_file("example.pdf").unwrap();
+    /// ```
+    pub fn print_pdf(&self, options: &PrintOptions) -> Result<Vec<u8>, WebdriverError> {
+        self.run(print_page(&self.session_id, options))
     }
 }
 
@@ -156,8 +350,9 @@ impl WebdriverObject for Tab {
 impl Drop for Tab {
     #[allow(unused_must_use)]
     fn drop(&mut self) {
-        if let Ok(()) = self.select() {
-            close_active_tab(&self.session_id);
+        if !self.close_on_drop {
+            return;
         }
+        self.run(close_active_tab(&self.session_id));
     }
 }
\ No newline at end of file
@@ -0,0 +1,132 @@
+//! Transports send the HTTP requests of the crate to the webdriver
+
+use std::error::Error;
+use std::sync::Arc;
+
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub enum Method {
+    Get,
+    Post,
+    Delete
+}
+
+impl Method {
+    pub fn to_string(self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Delete => "DELETE"
+        }
+    }
+
+    pub fn from(value: &str) -> Option<Method> {
+        match value {
+            "GET" => Some(Method::Get),
+            "POST" => Some(Method::Post),
+            "DELETE" => Some(Method::Delete),
+            _ => None
+        }
+    }
+}
+
+/// A request to the webdriver.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Request {
+    pub method: Method,
+    /// The full url, like `http://localhost:4444/session/<id>/url`.
+    pub url: String,
+    /// The JSON body of POST requests.
+    pub body: Option<String>
+}
+
+/// The response of the webdriver.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>
+}
+
+impl Response {
+    /// Build a response from a JSON text (useful to script responses).
+    pub fn new(status: u16, body: &str) -> Self {
+        Response {
+            status,
+            body: body.as_bytes().to_vec()
+        }
+    }
+}
+
+/// Something able to send HTTP requests.
+/// The default transport is [MinreqTransport](struct.MinreqTransport.html).
+/// Another transport can be used with [SessionBuilder::transport()](../session/struct.SessionBuilder.html#method.transport), for example to return scripted responses in tests.
+///
+/// An error returned by [send()](#tymethod.send) means that the request failed (the webdriver could not be reached).
+/// It becomes a [FailedRequest](../error/enum.ErrorKind.html#variant.FailedRequest) error.
+/// Errors reported by the webdriver are normal responses.
+///
+/// Sessions can be shared between threads, so transports must be `Send` and `Sync`.
+/// A transport wrapped in an `Arc` is a transport too, so it can still be used after being given to a session.
+///
+/// # Example
+///
+/// ```rust
+/// use lw_webdriver::{session::SessionBuilder, enums::Browser};
+/// use lw_webdriver::transport::{Transport, Request, Response, Method};
+/// use std::error::Error;
+///
+/// struct Scripted;
+///
+/// impl Transport for Scripted {
+///     fn send(&self, request: &Request) -> Result<Response, Box<dyn Error + Send + Sync>> {
+///         match (request.method, request.url.as_str()) {
+///             (Method::Post, "http://localhost:4444/session") => Ok(Response::new(200, r#"{"value": {"sessionId": "1", "capabilities": {}}}"#)),
+///             (Method::Get, "http://localhost:4444/session/1/window/handles") => Ok(Response::new(200, r#"{"value": ["tab"]}"#)),
+///             (Method::Get, "http://localhost:4444/session/1/window") => Ok(Response::new(200, r#"{"value": "tab"}"#)),
+///             (Method::Get, "http://localhost:4444/session/1/url") => Ok(Response::new(200, r#"{"value": "about:blank"}"#)),
+///             _ => Ok(Response::new(200, r#"{"value": null}"#))
+///         }
+///     }
+/// }
+///
+/// let session = SessionBuilder::new(Browser::Firefox).transport(Scripted).build().unwrap();
+/// assert_eq!(session.tabs[0].get_url().unwrap(), "about:blank");
+/// ```
+pub trait Transport: Send + Sync {
+    fn send(&self, request: &Request) -> Result<Response, Box<dyn Error + Send + Sync>>;
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn send(&self, request: &Request) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        (**self).send(request)
+    }
+}
+
+/// Send requests with [minreq](https://crates.io/crates/minreq).
+#[derive(Debug)]
+#[derive(Default)]
+pub struct MinreqTransport;
+
+impl Transport for MinreqTransport {
+    fn send(&self, request: &Request) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        let mut minreq_request = match request.method {
+            Method::Get => minreq::get(request.url.as_str()),
+            Method::Post => minreq::post(request.url.as_str()),
+            Method::Delete => minreq::delete(request.url.as_str())
+        };
+        if let Some(body) = &request.body {
+            minreq_request = minreq_request.with_body(body.as_str());
+        }
+        let response = minreq_request.send()?;
+
+        Ok(Response {
+            status: response.status_code as u16,
+            body: response.into_bytes()
+        })
+    }
+}
@@ -0,0 +1,208 @@
+//! Wait for a condition on a tab or an element instead of sleeping
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use std::result::Result;
+use log::debug;
+use crate::tab::Tab;
+use crate::elements::Element;
+use crate::enums::Selector;
+use crate::error::{WebdriverError, ErrorKind};
+
+/// Poll a condition until it is met or the timeout expires.
+/// Errors listed by [ignore()](#method.ignore) are treated as "not yet" (by default StaleElementReference, since pages often replace elements while loading).
+/// Other errors are returned immediately.
+/// When the timeout expires, a [Timeout](../error/enum.ErrorKind.html#variant.Timeout) error is returned, with the last ignored error as its source.
+///
+/// # Example
+///
+/// ```rust
+/// # use lw_webdriver::{session::Session, enums::{Browser, Selector}};
+/// use lw_webdriver::wait::Wait;
+/// use std::time::Duration;
+///
+/// let mut session = Session::new(Browser::Firefox, false).unwrap();
+/// session.tabs[0].execute_script("setTimeout(() => document.body.innerHTML = '<button>OK</button>', 500);", vec![]).unwrap();
+///
+/// let wait = Wait::new(Duration::from_secs(5));
+/// let button = wait.for_clickable(&session.tabs[0], Selector::Css, "button").unwrap();
+/// button.click().unwrap();
+///
+/// // custom conditions return Some when they are met
+/// let text = wait.until(|| {
+///     let text = button.get_text()?;
+///     Ok(if text.is_empty() { None } else { Some(text) })
+/// }).unwrap();
+/// ```
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Wait {
+    timeout: Duration,
+    interval: Duration,
+    ignored: Vec<ErrorKind>
+}
+
+impl Wait {
+    /// Poll every 100 milliseconds until the timeout.
+    pub fn new(timeout: Duration) -> Self {
+        Wait {
+            timeout,
+            interval: Duration::from_millis(100),
+            ignored: vec![ErrorKind::StaleElementReference]
+        }
+    }
+
+    /// Set the delay between two checks of the condition.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Retry when the condition returns this kind of error.
+    pub fn ignore(mut self, kind: ErrorKind) -> Self {
+        self.ignored.push(kind);
+        self
+    }
+
+    /// Poll a condition until it returns `Some` value.
+    pub fn until<T, F>(&self, condition: F) -> Result<T, WebdriverError> where F: FnMut() -> Result<Option<T>, WebdriverError> {
+        self.until_described("condition", condition)
+    }
+
+    /// Poll a condition until it returns `true`.
+    pub fn until_true<F>(&self, mut condition: F) -> Result<(), WebdriverError> where F: FnMut() -> Result<bool, WebdriverError> {
+        self.until(|| Ok(if condition()? { Some(()) } else { None }))
+    }
+
+    /// Wait for an element to be in the page and return it.
+    pub fn for_element(&self, tab: &Tab, selector: Selector, tofind: &str) -> Result<Element, WebdriverError> {
+        self.until_described(&format!("element {} to be present", tofind), || tab.find(selector, tofind))
+    }
+
+    /// Wait for an element to be in the page and displayed, and return it.
+    pub fn for_visible(&self, tab: &Tab, selector: Selector, tofind: &str) -> Result<Element, WebdriverError> {
+        self.until_described(&format!("element {} to be visible", tofind), || {
+            match tab.find(selector, tofind)? {
+                Some(element) if element.is_displayed()? => Ok(Some(element)),
+                _ => Ok(None)
+            }
+        })
+    }
+
+    /// Wait for an element to be displayed and enabled, and return it.
+    pub fn for_clickable(&self, tab: &Tab, selector: Selector, tofind: &str) -> Result<Element, WebdriverError> {
+        self.until_described(&format!("element {} to be clickable", tofind), || {
+            match tab.find(selector, tofind)? {
+                Some(element) if element.is_displayed()? && element.is_enabled()? => Ok(Some(element)),
+                _ => Ok(None)
+            }
+        })
+    }
+
+    /// Wait for the text of an element to contain a string, and return the element.
+    pub fn for_text(&self, tab: &Tab, selector: Selector, tofind: &str, text: &str) -> Result<Element, WebdriverError> {
+        self.until_described(&format!("element {} to contain {:?}", tofind, text), || {
+            match tab.find(selector, tofind)? {
+                Some(element) if element.get_text()?.contains(text) => Ok(Some(element)),
+                _ => Ok(None)
+            }
+        })
+    }
+
+    /// Wait for no element to match a selector anymore.
+    pub fn for_element_gone(&self, tab: &Tab, selector: Selector, tofind: &str) -> Result<(), WebdriverError> {
+        self.until_described(&format!("element {} to be gone", tofind), || {
+            Ok(match tab.find(selector, tofind)? {
+                Some(_) => None,
+                None => Some(())
+            })
+        })
+    }
+
+    /// Wait for the url of the tab to match a pattern, and return the url.
+    /// In the pattern, `*` matches any sequence of characters, so `"*/orders/*"` matches `"https://example.com/orders/42"`.
+    pub fn for_url(&self, tab: &Tab, pattern: &str) -> Result<String, WebdriverError> {
+        self.until_described(&format!("url to match {}", pattern), || {
+            let url = tab.get_url()?;
+            Ok(if wildcard_matches(pattern, &url) { Some(url) } else { None })
+        })
+    }
+
+    /// Wait for the title of the tab to be exactly this one.
+    pub fn for_title(&self, tab: &Tab, title: &str) -> Result<(), WebdriverError> {
+        self.until_described(&format!("title to be {:?}", title), || {
+            Ok(if tab.get_title()? == title { Some(()) } else { None })
+        })
+    }
+
+    fn until_described<T, F>(&self, description: &str, mut condition: F) -> Result<T, WebdriverError> where F: FnMut() -> Result<Option<T>, WebdriverError> {
+        let start = Instant::now();
+        let mut last_error = None;
+        loop {
+            match condition() {
+                Ok(Some(value)) => return Ok(value),
+                Ok(None) => (),
+                Err(error) if self.ignored.contains(&error.kind) => {
+                    debug!("ignoring error while waiting for {}: {}", description, error);
+                    last_error = Some(error);
+                },
+                Err(error) => return Err(error)
+            }
+
+            let elapsed = start.elapsed();
+            if elapsed >= self.timeout {
+                let error = WebdriverError::from(ErrorKind::Timeout)
+                    .with_message(format!("waited {:?} for {}", self.timeout, description));
+                return Err(match last_error {
+                    Some(last_error) => error.with_source(last_error),
+                    None => error
+                });
+            }
+            sleep(self.interval.min(self.timeout - elapsed));
+        }
+    }
+}
+
+/// Check if a text matches a pattern where `*` matches any sequence of characters
+fn wildcard_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    if !text.starts_with(first) {
+        return false;
+    }
+    let mut rest = &text[first.len()..];
+    let parts: Vec<&str> = parts.collect();
+    let (last, middle) = match parts.split_last() {
+        Some((last, middle)) => (*last, middle),
+        // no wildcard
+        None => return rest.is_empty()
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::wildcard_matches;
+
+    #[test]
+    fn wildcards() {
+        assert!(wildcard_matches("https://example.com/", "https://example.com/"));
+        assert!(!wildcard_matches("https://example.com/", "https://example.com/page"));
+        assert!(wildcard_matches("*", ""));
+        assert!(wildcard_matches("*/orders/*", "https://example.com/orders/42"));
+        assert!(wildcard_matches("https://*.com/*", "https://example.com/"));
+        assert!(!wildcard_matches("*/orders/*", "https://example.com/order/42"));
+        assert!(wildcard_matches("*.pdf", "report.pdf"));
+        assert!(!wildcard_matches("*.pdf", "report.pdf.html"));
+        // the parts must not overlap
+        assert!(!wildcard_matches("ab*ba", "aba"));
+        assert!(wildcard_matches("a*b*c", "aXbYc"));
+        assert!(!wildcard_matches("a*b*c", "acb"));
+    }
+}
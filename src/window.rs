@@ -0,0 +1,57 @@
+//! The position and the size of the browser window
+
+use json::*;
+
+/// The position and the size of a window in screen pixels, including the borders and the toolbars of the browser.
+///
+/// # Example
+///
+/// ```rust
+/// # use lw_webdriver::{session::Session, enums::Browser};
+/// let mut session = Session::new(Browser::Firefox, false).unwrap();
+///
+/// // the same layout on every run
+/// session.tabs[0].set_viewport_size(1280, 720).unwrap();
+/// let rect = session.tabs[0].get_window_rect().unwrap();
+/// assert!(rect.width >= 1280);
+/// ```
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub struct WindowRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32
+}
+
+impl WindowRect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        WindowRect {
+            x,
+            y,
+            width,
+            height
+        }
+    }
+
+    /// Read a rect sent by the webdriver.
+    /// Return `None` if a field is missing.
+    pub fn from_json(json: &JsonValue) -> Option<WindowRect> {
+        Some(WindowRect {
+            x: json["x"].as_f64()? as i32,
+            y: json["y"].as_f64()? as i32,
+            width: json["width"].as_f64()? as u32,
+            height: json["height"].as_f64()? as u32
+        })
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        object!{
+            "x" => self.x,
+            "y" => self.y,
+            "width" => self.width,
+            "height" => self.height
+        }
+    }
+}
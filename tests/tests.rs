@@ -5,6 +5,98 @@ use lw_webdriver::enums::*;
 use std::panic::catch_unwind;
 use json::object;
 use log::{info};
+use lw_webdriver::transport::{Transport, Request, Response, Method};
+use std::{error::Error, sync::Mutex};
+
+/// A request received by a [Scripted] webdriver
+struct Call {
+    method: Method,
+    /// The path of the url, like `/session/s1/title`
+    path: String,
+    body: json::JsonValue,
+    /// The handle of the selected window
+    window: String
+}
+
+/// A fake webdriver for the tests which don't need a browser.
+/// It creates the session `s1` with some windows and keeps track of the selected one.
+/// Requests are answered by the routes of the test, or with a null value.
+struct Scripted<F> {
+    windows: Vec<String>,
+    selected: Mutex<String>,
+    errors: Vec<(Method, &'static str, &'static str)>,
+    requests: Mutex<Vec<Request>>,
+    routes: F
+}
+
+/// Create a fake webdriver with these windows (the first one is selected)
+fn scripted<F>(windows: &[&str], routes: F) -> Scripted<F> where F: Fn(&Call) -> Option<Response> + Send + Sync {
+    Scripted {
+        windows: windows.iter().map(|w| w.to_string()).collect(),
+        selected: Mutex::new(windows[0].to_string()),
+        errors: Vec::new(),
+        requests: Mutex::new(Vec::new()),
+        routes
+    }
+}
+
+impl<F> Scripted<F> {
+    /// Fail the requests to this path, like a webdriver which can't be reached
+    fn error(mut self, method: Method, path: &'static str, message: &'static str) -> Self {
+        self.errors.push((method, path, message));
+        self
+    }
+
+    /// Return every request received so far
+    fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl<F> Scripted<F> where F: Fn(&Call) -> Option<Response> + Send + Sync {
+    fn answer(&self, request: &Request) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        self.requests.lock().unwrap().push(request.clone());
+        let host = request.url.find("://").map(|p| p + 3).unwrap_or(0);
+        let path = request.url[host..].find('/').map(|p| &request.url[host + p..]).unwrap_or("/");
+        if let Some((_, _, message)) = self.errors.iter().find(|(method, error_path, _)| *method == request.method && *error_path == path) {
+            return Err((*message).into());
+        }
+        let body = request.body.as_deref().and_then(|body| json::parse(body).ok()).unwrap_or(json::JsonValue::Null);
+        let mut selected = self.selected.lock().unwrap();
+        if request.method == Method::Post && path == "/session/s1/window" {
+            *selected = body["handle"].to_string();
+        }
+        let call = Call { method: request.method, path: path.to_string(), body, window: selected.clone() };
+        if let Some(response) = (self.routes)(&call) {
+            return Ok(response);
+        }
+        Ok(match (request.method, path) {
+            (Method::Post, "/session") => Response::new(200, r#"{"value": {"sessionId": "s1", "capabilities": {}}}"#),
+            (Method::Get, "/session/s1/window/handles") => Response::new(200, &json::stringify(object!{ "value" => self.windows.clone() })),
+            (Method::Get, "/session/s1/window") => Response::new(200, &json::stringify(object!{ "value" => selected.as_str() })),
+            _ => Response::new(200, r#"{"value": null}"#)
+        })
+    }
+}
+
+impl<F> Transport for Scripted<F> where F: Fn(&Call) -> Option<Response> + Send + Sync {
+    fn send(&self, request: &Request) -> Result<Response, Box<dyn Error + Send + Sync>> {
+        // let other threads run in the middle of commands
+        std::thread::yield_now();
+        self.answer(request)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<F> lw_webdriver::asynchronous::AsyncTransport for Scripted<F> where F: Fn(&Call) -> Option<Response> + Send + Sync {
+    fn send<'a>(&'a self, request: &'a Request) -> lw_webdriver::asynchronous::TransportFuture<'a> {
+        Box::pin(async move {
+            // let other tasks run in the middle of commands
+            tokio::task::yield_now().await;
+            self.answer(request)
+        })
+    }
+}
 
 #[test]
 fn navigation() {
@@ -13,7 +105,7 @@ fn navigation() {
     });
     
     for i in 0..2 {
-        let mut session = match i {
+        let session = match i {
             0 => {
                 info!("testing with Firefox");
                 Session::new(Browser::Firefox, false).unwrap()
@@ -116,6 +208,7 @@ fn timeouts() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn elements() {
     catch_unwind(|| {
         env_logger::init();
@@ -137,8 +230,8 @@ fn elements() {
         session.tabs[0].navigate("https://www.mozilla.org/fr/").unwrap();
         session.tabs[1].navigate("https://mubelotix.dev/").unwrap();
 
-        let mut email_input = session.tabs[0].find(Selector::XPath, "//*[@id=\"id_email\"]").unwrap().unwrap();
-        let mut label = session.tabs[0].find(Selector::XPath, "/html/body/div[3]/main/div[1]/div/aside/div[2]/form/fieldset/div/fieldset/p/label[2]").unwrap().unwrap();
+        let email_input = session.tabs[0].find(Selector::XPath, "//*[@id=\"id_email\"]").unwrap().unwrap();
+        let label = session.tabs[0].find(Selector::XPath, "/html/body/div[3]/main/div[1]/div/aside/div[2]/form/fieldset/div/fieldset/p/label[2]").unwrap().unwrap();
         assert_eq!(email_input.get_tag_name().unwrap(), "input");
         assert_eq!(email_input.is_enabled().unwrap(), true);
         assert!(email_input.get_rect().is_ok());
@@ -149,6 +242,16 @@ fn elements() {
         assert_eq!(project_element.get_css_value("display").unwrap(), "flex");
         assert_eq!(project_element.get_property("draggable").unwrap(), "false");
 
+        let projects = session.tabs[1].find_all(Selector::Css, "main>div.project").unwrap();
+        assert!(projects.len() > 1);
+        assert!(projects[0] == project_element);
+        assert!(session.tabs[1].find_all(Selector::Css, "#does_not_exist").unwrap().is_empty());
+
+        let project_children = project_element.find_all(Selector::XPath, "./*").unwrap();
+        assert!(!project_children.is_empty());
+        assert!(project_element.find(Selector::Css, "div").unwrap().is_some());
+        assert!(project_element.find(Selector::Css, "#does_not_exist").unwrap().is_none());
+
         email_input.type_text("test@example.com").unwrap();
         assert_eq!("Texte", label.get_text().unwrap());
         label.click().unwrap();
@@ -157,6 +260,8 @@ fn elements() {
 
 #[test]
 fn execute_javascript() {
+    use lw_webdriver::error::ErrorKind;
+
     catch_unwind(|| {
         env_logger::init();
     });
@@ -176,6 +281,26 @@ fn execute_javascript() {
         session.tabs[0].navigate("http://example.com").unwrap();
         let element = session.tabs[0].find(Selector::Css, "html>body>div>p>a").unwrap().unwrap();
         session.tabs[0].execute_script("arguments[0].click();", vec![element.as_json_object()]).unwrap();
+
+        session.tabs[0].navigate("http://example.com").unwrap();
+        let count = session.tabs[0].execute_script("return document.querySelectorAll('p').length;", vec![]).unwrap();
+        assert_eq!(count.as_usize(), Some(2));
+        let title = session.tabs[0].execute_script("return document.title;", vec![]).unwrap();
+        assert_eq!(title.as_str(), Some("Example Domain"));
+        let object = session.tabs[0].execute_script("return {sum: arguments[0] + arguments[1], link: document.querySelector('a')};", vec![1.into(), 2.into()]).unwrap();
+        assert_eq!(object.get("sum").unwrap().as_i64(), Some(3));
+        assert_eq!(object.get("link").unwrap().as_element().unwrap().get_tag_name().unwrap(), "a");
+        let paragraphs = session.tabs[0].execute_script("return document.querySelectorAll('p');", vec![]).unwrap();
+        assert_eq!(paragraphs.into_elements().unwrap().len(), 2);
+
+        let value = session.tabs[0].execute_async_script("let done = arguments[arguments.length - 1]; setTimeout(() => done(arguments[0] * 2), 100);", vec![21.into()]).unwrap();
+        assert_eq!(value.as_i64(), Some(42));
+
+        let mut timeouts = session.get_timeouts().unwrap();
+        timeouts.script = Some(200);
+        session.set_timeouts(timeouts).unwrap();
+        let error = session.tabs[0].execute_async_script("setTimeout(arguments[0], 10000);", vec![]).err().unwrap();
+        assert_eq!(error, ErrorKind::ScriptTimeoutError);
     }
 }
 
@@ -186,7 +311,7 @@ fn element_obscured() {
     });
     
     for i in 0..2 {
-        let mut session = match i {
+        let session = match i {
             0 => {
                 info!("testing with Firefox");
                 Session::new(Browser::Firefox, false).unwrap()
@@ -199,7 +324,1203 @@ fn element_obscured() {
 
         session.tabs[0].navigate("https://mubelotix.dev/webdriver_tests/element_obscured.html").unwrap();
         
-        let mut element_obscured = session.tabs[0].find(Selector::XPath, "/html/body/p/a").unwrap().unwrap();
+        let element_obscured = session.tabs[0].find(Selector::XPath, "/html/body/p/a").unwrap().unwrap();
         element_obscured.click().unwrap();
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn webdriver_url() {
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    // a server behind a path prefix, answering the first request with an error and returning its request line
+    for prefix in ["/wd/hub", "/wd/hub/"].iter() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            reader.by_ref().take(content_length).read_to_end(&mut Vec::new()).unwrap();
+            let body = r#"{"value": {"error": "session not created", "message": "no browser", "stacktrace": ""}}"#;
+            write!(reader.get_mut(), "HTTP/1.1 500 Internal Server Error\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body).unwrap();
+            request_line
+        });
+
+        let result = Session::new_with_url(&format!("http://127.0.0.1:{}{}", port, prefix), Browser::Firefox, false);
+        assert!(result.is_err());
+        assert_eq!(server.join().unwrap().trim_end(), "POST /wd/hub/session HTTP/1.1");
+    }
+}
+
+#[test]
+fn capabilities() {
+    use lw_webdriver::capabilities::*;
+
+    let mut capabilities = Capabilities::new();
+    capabilities.browser_version = Some("80".to_string());
+    capabilities.accept_insecure_certs = Some(true);
+    capabilities.page_load_strategy = Some(PageLoadStrategy::Eager);
+    capabilities.unhandled_prompt_behavior = Some(UnhandledPromptBehavior::DismissAndNotify);
+    capabilities.proxy = Some(Proxy::Manual{ftp: None, http: Some("localhost:8080".to_string()), ssl: None, socks: None, socks_version: None, no_proxy: vec!["example.com".to_string()]});
+    capabilities.set_extension("moz:firefoxOptions", object!{ "args" => json::array!{"-private"} });
+
+    let mut first_match = Capabilities::new();
+    first_match.platform_name = Some("mac".to_string());
+
+    let json = SessionBuilder::new(Browser::Firefox)
+        .headless(true)
+        .always_match(capabilities)
+        .first_match(first_match)
+        .to_json()
+        .unwrap();
+
+    let always_match = &json["capabilities"]["alwaysMatch"];
+    assert_eq!(always_match["browserName"], "firefox");
+    assert_eq!(always_match["browserVersion"], "80");
+    assert_eq!(always_match["acceptInsecureCerts"], true);
+    assert_eq!(always_match["pageLoadStrategy"], "eager");
+    assert_eq!(always_match["unhandledPromptBehavior"], "dismiss and notify");
+    assert_eq!(always_match["proxy"], object!{ "proxyType" => "manual", "httpProxy" => "localhost:8080", "noProxy" => json::array!{"example.com"} });
+    assert_eq!(always_match["moz:firefoxOptions"]["args"], json::array!{"-private", "-headless"});
+    assert!(always_match["strictFileInteractability"].is_null());
+    assert_eq!(json["capabilities"]["firstMatch"][0], object!{ "platformName" => "mac" });
+    // the platform name is set by firstMatch, so it must not be in alwaysMatch
+    assert!(!always_match.has_key("platformName"));
+
+    // otherwise it is filled automatically
+    let json = SessionBuilder::new(Browser::Chrome).to_json().unwrap();
+    assert_eq!(json["capabilities"]["alwaysMatch"]["browserName"], "chrome");
+    assert!(json["capabilities"]["alwaysMatch"]["platformName"].is_string());
+
+    // headless is merged into the browser options set by firstMatch
+    let mut firefox = Capabilities::new();
+    firefox.set_extension("moz:firefoxOptions", object!{ "args" => json::array!{"-private"} });
+    let mut chrome = Capabilities::new();
+    chrome.browser_name = Some(Browser::Chrome);
+    let json = SessionBuilder::new(Browser::Firefox)
+        .headless(true)
+        .first_match(firefox)
+        .first_match(Capabilities::new())
+        .first_match(chrome)
+        .to_json()
+        .unwrap();
+    let always_match = &json["capabilities"]["alwaysMatch"];
+    let first_match = &json["capabilities"]["firstMatch"];
+    assert!(!always_match.has_key("moz:firefoxOptions"));
+    assert_eq!(first_match[0]["moz:firefoxOptions"]["args"], json::array!{"-private", "-headless"});
+    assert_eq!(first_match[1]["moz:firefoxOptions"]["args"], json::array!{"-headless"});
+    assert!(!first_match[2].has_key("moz:firefoxOptions"));
+    // the options of the other browser are not set by firstMatch
+    assert_eq!(always_match["goog:chromeOptions"]["args"], json::array!{"-headless"});
+
+    // and the browser set by the capabilities is the one made headless
+    let mut chrome = Capabilities::new();
+    chrome.browser_name = Some(Browser::Chrome);
+    let json = SessionBuilder::new(Browser::Firefox).headless(true).always_match(chrome).to_json().unwrap();
+    assert_eq!(json["capabilities"]["alwaysMatch"]["goog:chromeOptions"]["args"], json::array!{"-headless"});
+    assert!(!json["capabilities"]["alwaysMatch"].has_key("moz:firefoxOptions"));
+}
+
+#[test]
+fn driver_service() {
+    use lw_webdriver::{service::DriverServiceBuilder, error::ErrorKind};
+    use std::time::Duration;
+
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    let result = DriverServiceBuilder::new(Browser::Firefox).binary("./does_not_exist").start();
+    assert_eq!(result.err().unwrap(), ErrorKind::DriverNotFound);
+
+    // a driver exiting immediately is detected without waiting for the deadline
+    if cfg!(unix) {
+        let result = DriverServiceBuilder::new(Browser::Firefox)
+            .binary("/bin/false")
+            .startup_timeout(Duration::from_secs(60))
+            .start();
+        assert!(matches!(result.err().unwrap().kind, ErrorKind::FailedToLaunchDriver(_)));
+    }
+
+    // the output of the driver is attached to the error
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::write("./fake_driver.sh", "#!/bin/sh\necho \"listening on $2\"\necho \"fatal: no browser\" >&2\nexit 1\n").unwrap();
+        std::fs::set_permissions("./fake_driver.sh", std::fs::Permissions::from_mode(0o755)).unwrap();
+        let result = DriverServiceBuilder::new(Browser::Firefox)
+            .binary("./fake_driver.sh")
+            .start();
+        std::fs::remove_file("./fake_driver.sh");
+        let error = result.err().unwrap();
+        assert!(matches!(error.kind, ErrorKind::FailedToLaunchDriver(_)));
+        let output = error.driver_output().unwrap();
+        assert!(output.contains("fatal: no browser"));
+    }
+}
+
+#[test]
+fn errors() {
+    use lw_webdriver::error::ErrorKind;
+    use std::error::Error;
+
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    // nothing is listening on this port
+    let error = Session::new_with_url("http://localhost:1", Browser::Firefox, false).err().unwrap();
+    assert_eq!(error, ErrorKind::FailedRequest);
+    assert_eq!(error.command(), Some("POST /session"));
+    assert_eq!(error.status(), None);
+    assert!(error.source().is_some());
+    assert!(error.to_string().starts_with("failed request (POST /session)"));
+
+    let boxed: Box<dyn Error> = Box::new(error);
+    assert!(boxed.source().is_some());
+}
+
+#[test]
+fn quit() {
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    for i in 0..2 {
+        let mut session = match i {
+            0 => {
+                info!("testing with Firefox");
+                Session::new(Browser::Firefox, false).unwrap()
+            },
+            _ => {
+                info!("testing with Chrome");
+                Session::new(Browser::Chrome, false).unwrap()
+            }
+        };
+
+        session.open_tab().unwrap();
+        session.quit().unwrap();
+    }
+}
+
+#[test]
+fn cookies() {
+    use lw_webdriver::cookies::{Cookie, SameSite};
+
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    for i in 0..2 {
+        let session = match i {
+            0 => {
+                info!("testing with Firefox");
+                Session::new(Browser::Firefox, false).unwrap()
+            },
+            _ => {
+                info!("testing with Chrome");
+                Session::new(Browser::Chrome, false).unwrap()
+            }
+        };
+
+        session.tabs[0].navigate("https://mubelotix.dev/").unwrap();
+        session.tabs[0].delete_all_cookies().unwrap();
+        assert!(session.tabs[0].get_cookies().unwrap().is_empty());
+
+        // session cookies have no expiry
+        session.tabs[0].set_cookie(&Cookie::new("session", "42").same_site(SameSite::Lax)).unwrap();
+        session.tabs[0].set_cookie(&Cookie::new("persistent", "yes").path("/").expiry(4_000_000_000)).unwrap();
+        assert_eq!(session.tabs[0].get_cookies().unwrap().len(), 2);
+
+        let cookie = session.tabs[0].get_cookie("session").unwrap().unwrap();
+        assert_eq!(cookie.value, "42");
+        assert_eq!(cookie.expiry, None);
+        assert_eq!(cookie.same_site, Some(SameSite::Lax));
+        assert_eq!(session.tabs[0].get_cookie("persistent").unwrap().unwrap().expiry, Some(4_000_000_000));
+        assert_eq!(session.tabs[0].get_cookie("does not exist").unwrap(), None);
+
+        session.tabs[0].delete_cookie("session").unwrap();
+        assert_eq!(session.tabs[0].get_cookie("session").unwrap(), None);
+        session.tabs[0].delete_all_cookies().unwrap();
+        assert!(session.tabs[0].get_cookies().unwrap().is_empty());
+    }
+}
+
+#[test]
+fn cookie_json() {
+    use lw_webdriver::cookies::{Cookie, SameSite};
+
+    // session cookies have no expiry
+    let cookie = Cookie::new("session", "42").same_site(SameSite::Lax);
+    let json = cookie.to_json();
+    assert_eq!(json["name"], "session");
+    assert_eq!(json["sameSite"], "Lax");
+    assert!(!json.has_key("expiry"));
+    assert!(!json.has_key("path"));
+    assert_eq!(Cookie::from_json(&json), Some(cookie));
+
+    let cookie = Cookie::new("persistent", "yes").path("/").domain(".example.com").secure(true).http_only(true).expiry(4_000_000_000);
+    let json = cookie.to_json();
+    assert_eq!(json["expiry"], 4_000_000_000u64);
+    assert_eq!(json["httpOnly"], true);
+    assert_eq!(Cookie::from_json(&json), Some(cookie));
+
+    // fields sent by browsers are read leniently
+    let cookie = Cookie::from_json(&object!{ "name" => "a", "value" => "b", "sameSite" => "strict" }).unwrap();
+    assert_eq!(cookie.same_site, Some(SameSite::Strict));
+    assert!(!cookie.secure);
+    assert_eq!(Cookie::from_json(&object!{ "name" => "a" }), None);
+    assert_eq!(SameSite::from("unknown"), None);
+}
+
+#[test]
+fn cookie_jar() {
+    use lw_webdriver::cookies::{Cookie, CookieJar, SameSite};
+
+    let jar = CookieJar::from_cookies(vec![
+        Cookie::new("session", "42").domain("example.com").path("/").http_only(true).same_site(SameSite::Lax),
+        Cookie::new("theme", "dark").domain(".example.com").path("/").secure(true).expiry(4_000_000_000),
+        Cookie::new("other", "1").domain("mubelotix.dev").path("/")
+    ]);
+
+    let text = jar.to_netscape();
+    assert!(text.contains("#HttpOnly_example.com\tFALSE\t/\tFALSE\t0\tsession\t42"));
+    assert!(text.contains(".example.com\tTRUE\t/\tTRUE\t4000000000\ttheme\tdark"));
+    let parsed = CookieJar::from_netscape(&text).unwrap();
+    assert_eq!(parsed.cookies.len(), 3);
+    assert_eq!(parsed.cookies[0].expiry, None);
+    assert!(parsed.cookies[0].http_only);
+    assert_eq!(parsed.cookies[0].same_site, None); // not part of the format
+    assert_eq!(parsed.cookies[1], jar.cookies[1]);
+    assert!(CookieJar::from_netscape("example.com\tFALSE\t/").is_err());
+    let parsed = CookieJar::from_netscape("example.com\tTRUE\t/\tFALSE\t0\tlang\ten").unwrap();
+    assert_eq!(parsed.cookies[0].domain.as_deref(), Some(".example.com"));
+
+    assert_eq!(CookieJar::from_json(&jar.to_json()).unwrap(), jar);
+    assert!(CookieJar::from_json(&object!{ "name" => "a" }).is_err());
+
+    let names = |host| jar.cookies_for_domain(host).iter().map(|c| c.name.as_str()).collect::<Vec<&str>>();
+    assert_eq!(names("example.com"), vec!["session", "theme"]);
+    assert_eq!(names("www.example.com"), vec!["theme"]);
+    assert_eq!(names("WWW.Example.com"), vec!["theme"]);
+    assert_eq!(names("éxample.com"), Vec::<&str>::new());
+    assert_eq!(names("www.éxample.com"), Vec::<&str>::new());
+    assert_eq!(names("notexample.com"), Vec::<&str>::new());
+    assert_eq!(names("mubelotix.dev"), vec!["other"]);
+}
+
+#[test]
+fn screenshots() {
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    for i in 0..2 {
+        let session = match i {
+            0 => {
+                info!("testing with Firefox");
+                Session::new(Browser::Firefox, false).unwrap()
+            },
+            _ => {
+                info!("testing with Chrome");
+                Session::new(Browser::Chrome, false).unwrap()
+            }
+        };
+
+        session.tabs[0].navigate("http://example.com/").unwrap();
+        let screenshot = session.tabs[0].screenshot().unwrap();
+        assert_eq!(&screenshot.as_bytes()[..8], b"\x89PNG\r\n\x1a\n");
+
+        let title = session.tabs[0].find(Selector::Css, "h1").unwrap().unwrap();
+        let element_screenshot = title.screenshot().unwrap();
+        assert_eq!(&element_screenshot.as_bytes()[..8], b"\x89PNG\r\n\x1a\n");
+        assert!(element_screenshot.as_bytes().len() < screenshot.as_bytes().len());
+
+        screenshot.save("screenshot_test.png").unwrap();
+        assert_eq!(std::fs::read("screenshot_test.png").unwrap(), screenshot.into_bytes());
+        std::fs::remove_file("screenshot_test.png").unwrap();
+    }
+}
+
+#[test]
+fn screenshot_decoding() {
+    use lw_webdriver::error::ErrorKind;
+
+    // the PNG signature as screenshots, and invalid base64 for elements
+    let webdriver = scripted(&["w1"], |call| match (call.method, call.path.as_str()) {
+        (Method::Get, "/session/s1/screenshot") => Some(Response::new(200, r#"{"value": "iVBORw0K\nGgo="}"#)),
+        (Method::Post, "/session/s1/element") => Some(Response::new(200, r#"{"value": {"element-6066-11e4-a52e-4f735466cecf": "e1"}}"#)),
+        (Method::Get, "/session/s1/element/e1/screenshot") => Some(Response::new(200, r#"{"value": "not base64!"}"#)),
+        _ => None
+    });
+
+    let session = SessionBuilder::new(Browser::Firefox).transport(webdriver).build().unwrap();
+    let screenshot = session.tabs[0].screenshot().unwrap();
+    assert_eq!(screenshot.as_bytes(), b"\x89PNG\r\n\x1a\n");
+
+    let element = session.tabs[0].find(Selector::Css, "h1").unwrap().unwrap();
+    assert_eq!(element.screenshot().err().unwrap(), ErrorKind::InvalidResponse);
+}
+
+#[test]
+fn print_options() {
+    use lw_webdriver::print::*;
+
+    let json = PrintOptions::default().to_json();
+    assert_eq!(json["orientation"], "portrait");
+    assert_eq!(json["scale"], 1.0);
+    assert_eq!(json["background"], false);
+    assert_eq!(json["page"]["width"], 21.59);
+    assert_eq!(json["page"]["height"], 27.94);
+    assert_eq!(json["margin"]["left"], 1.0);
+    assert_eq!(json["pageRanges"].len(), 0);
+    assert_eq!(json["shrinkToFit"], true);
+
+    let options = PrintOptions {
+        orientation: Orientation::Landscape,
+        page: PageSize::A4,
+        margin: Margins::uniform(0.0),
+        page_ranges: vec![PageRange::Page(1), PageRange::Range(3, 5)],
+        ..PrintOptions::default()
+    };
+    let json = options.to_json();
+    assert_eq!(json["orientation"], "landscape");
+    assert_eq!(json["page"]["height"], 29.7);
+    assert_eq!(json["margin"]["top"], 0.0);
+    assert_eq!(json["pageRanges"][0], 1);
+    assert_eq!(json["pageRanges"][1], "3-5");
+}
+
+#[test]
+fn print_pdf() {
+    use lw_webdriver::print::*;
+
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    for i in 0..2 {
+        let session = match i {
+            0 => {
+                info!("testing with Firefox");
+                Session::new(Browser::Firefox, true).unwrap()
+            },
+            _ => {
+                info!("testing with Chrome");
+                Session::new(Browser::Chrome, true).unwrap()
+            }
+        };
+
+        session.tabs[0].execute_script("document.body.innerHTML = '<h1>Invoice</h1>';", vec![]).unwrap();
+        let pdf = session.tabs[0].print_pdf(&PrintOptions::default()).unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+
+        let options = PrintOptions {
+            orientation: Orientation::Landscape,
+            page: PageSize::A4,
+            margin: Margins::uniform(0.0),
+            page_ranges: vec![PageRange::Page(1)],
+            ..PrintOptions::default()
+        };
+        assert!(session.tabs[0].print_pdf(&options).unwrap().starts_with(b"%PDF"));
+    }
+}
+
+#[test]
+fn actions_json() {
+    use lw_webdriver::actions::*;
+    use std::time::Duration;
+
+    let actions = Actions::new()
+        .key(KeySource::new("keyboard").key_down('a').pause(None).key_up('a'))
+        .pointer(PointerSource::new("finger", PointerType::Touch).move_to(10, -5, Origin::Viewport, Duration::from_millis(100)).click(MouseButton::Left))
+        .wheel(WheelSource::new("wheel").pause(Some(Duration::from_millis(20))).scroll(0, 0, 0, 300, Origin::Viewport, Duration::from_millis(0)));
+    let json = actions.to_json();
+    assert_eq!(json["actions"][0]["type"], "key");
+    assert_eq!(json["actions"][0]["actions"][0]["value"], "a");
+    assert!(!json["actions"][0]["actions"][1].has_key("duration"));
+    assert_eq!(json["actions"][1]["parameters"]["pointerType"], "touch");
+    assert_eq!(json["actions"][1]["actions"][0]["y"], -5);
+    assert_eq!(json["actions"][1]["actions"][0]["origin"], "viewport");
+    assert_eq!(json["actions"][1]["actions"][2]["type"], "pointerUp");
+    assert_eq!(json["actions"][2]["actions"][0]["duration"], 20);
+    assert_eq!(json["actions"][2]["actions"][1]["deltaY"], 300);
+}
+
+#[test]
+fn actions() {
+    use lw_webdriver::{actions::*, keys::Keys};
+
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    for i in 0..2 {
+        let session = match i {
+            0 => {
+                info!("testing with Firefox");
+                Session::new(Browser::Firefox, true).unwrap()
+            },
+            _ => {
+                info!("testing with Chrome");
+                Session::new(Browser::Chrome, true).unwrap()
+            }
+        };
+
+        session.tabs[0].execute_script("document.body.innerHTML = '<input id=\"input\"><div id=\"target\" style=\"width:100px;height:100px\"></div>'; \
+            window.events = []; \
+            for (const type of ['mouseover', 'dblclick', 'contextmenu']) target.addEventListener(type, e => { e.preventDefault(); window.events.push(type); });", vec![]).unwrap();
+
+        let input = session.tabs[0].find(Selector::Css, "#input").unwrap().unwrap();
+        input.click().unwrap();
+        session.tabs[0].perform_actions(&Actions::new()
+            .key(KeySource::new("keyboard").key_down(Keys::Shift).key_down('a').key_up('a').key_up(Keys::Shift))).unwrap();
+        session.tabs[0].release_actions().unwrap();
+        assert_eq!(session.tabs[0].execute_script("return input.value;", vec![]).unwrap().as_str(), Some("A"));
+
+        let target = session.tabs[0].find(Selector::Css, "#target").unwrap().unwrap();
+        target.hover().unwrap();
+        target.double_click().unwrap();
+        target.right_click().unwrap();
+        let events = session.tabs[0].execute_script("return window.events;", vec![]).unwrap().into_array().unwrap();
+        for event in &["mouseover", "dblclick", "contextmenu"] {
+            assert!(events.iter().any(|e| e.as_str() == Some(event)));
+        }
+    }
+}
+
+#[test]
+fn key_sequence() {
+    use lw_webdriver::keys::*;
+    use lw_webdriver::actions::*;
+
+    assert_eq!(char::from(Keys::Enter), '\u{E007}');
+    assert_eq!(Keys::NumpadDelete.to_char(), '\u{E05D}');
+    assert_eq!(format!("{}{}", Keys::F1, Keys::F12), "\u{E031}\u{E03C}");
+    assert!(Keys::RightControl.is_modifier());
+    assert!(!Keys::Tab.is_modifier());
+
+    let sequence = KeySequence::new().chord(&[Keys::Control, Keys::Shift], "z").key(Keys::Tab);
+    assert_eq!(String::from(sequence.clone()), "\u{E009}\u{E008}z\u{E000}\u{E004}");
+    assert_eq!(KeySequence::new().text("abc").key(Keys::Backspace).chord(&[Keys::Shift], "d").into_string(), "abc\u{E003}\u{E008}d\u{E000}");
+
+    let json = Actions::new().key(KeySource::new("keyboard").type_text(sequence.as_str())).to_json();
+    let actions: Vec<(String, String)> = json["actions"][0]["actions"].members()
+        .map(|a| (a["type"].to_string(), a["value"].to_string()))
+        .collect();
+    let expected = [
+        ("keyDown", "\u{E009}"), ("keyDown", "\u{E008}"), ("keyDown", "z"), ("keyUp", "z"),
+        ("keyUp", "\u{E008}"), ("keyUp", "\u{E009}"), ("keyDown", "\u{E004}"), ("keyUp", "\u{E004}")
+    ];
+    assert_eq!(actions.len(), expected.len());
+    for (action, expected) in actions.iter().zip(expected.iter()) {
+        assert_eq!((action.0.as_str(), action.1.as_str()), *expected);
+    }
+}
+
+#[test]
+fn keys() {
+    use lw_webdriver::keys::*;
+    use lw_webdriver::actions::*;
+
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    for i in 0..2 {
+        let session = match i {
+            0 => {
+                info!("testing with Firefox");
+                Session::new(Browser::Firefox, true).unwrap()
+            },
+            _ => {
+                info!("testing with Chrome");
+                Session::new(Browser::Chrome, true).unwrap()
+            }
+        };
+
+        session.tabs[0].execute_script("document.body.innerHTML = '<input id=\"input\">';", vec![]).unwrap();
+        let input = session.tabs[0].find(Selector::Css, "#input").unwrap().unwrap();
+        input.type_text(KeySequence::new().text("abc").key(Keys::Backspace).chord(&[Keys::Shift], "d").as_str()).unwrap();
+        assert_eq!(session.tabs[0].execute_script("return input.value;", vec![]).unwrap().as_str(), Some("abD"));
+
+        session.tabs[0].perform_actions(&Actions::new().key(KeySource::new("keyboard").type_text(&format!("{}{}e", Keys::ArrowLeft, Keys::Delete)))).unwrap();
+        assert_eq!(session.tabs[0].execute_script("return input.value;", vec![]).unwrap().as_str(), Some("abe"));
+    }
+}
+
+#[test]
+fn alerts() {
+    use lw_webdriver::error::ErrorKind;
+    use std::{thread::sleep, time::Duration};
+
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    for i in 0..2 {
+        let session = match i {
+            0 => {
+                info!("testing with Firefox");
+                Session::new(Browser::Firefox, true).unwrap()
+            },
+            _ => {
+                info!("testing with Chrome");
+                Session::new(Browser::Chrome, true).unwrap()
+            }
+        };
+
+        assert!(session.tabs[0].alert().unwrap().is_none());
+
+        session.tabs[0].execute_script("setTimeout(() => window.answer = confirm('Are you sure?'));", vec![]).unwrap();
+        sleep(Duration::from_millis(200));
+        let alert = session.tabs[0].alert().unwrap().unwrap();
+        assert_eq!(alert.text().unwrap(), "Are you sure?");
+        alert.dismiss().unwrap();
+        assert_eq!(session.tabs[0].execute_script("return window.answer;", vec![]).unwrap().as_bool(), Some(false));
+
+        session.tabs[0].execute_script("setTimeout(() => window.answer = prompt('Name?'));", vec![]).unwrap();
+        sleep(Duration::from_millis(200));
+        let alert = session.tabs[0].alert().unwrap().unwrap();
+        alert.send_keys("Alice").unwrap();
+        alert.accept().unwrap();
+        assert_eq!(session.tabs[0].execute_script("return window.answer;", vec![]).unwrap().as_str(), Some("Alice"));
+
+        // the text of an unexpected dialog is reported in the error
+        session.tabs[0].execute_script("setTimeout(() => alert('Unexpected'));", vec![]).unwrap();
+        sleep(Duration::from_millis(200));
+        let error = session.tabs[0].find(Selector::Css, "body").err().unwrap();
+        assert_eq!(error, ErrorKind::UnexpectedAlertOpen);
+        assert_eq!(error.alert_text(), Some("Unexpected"));
+        if let Some(alert) = session.tabs[0].alert().unwrap() {
+            alert.accept().unwrap();
+        }
+    }
+}
+
+#[test]
+fn alert_errors() {
+    use lw_webdriver::error::ErrorKind;
+
+    // a dialog which was not opened by the test is shown
+    let webdriver = scripted(&["w1"], |call| match (call.method, call.path.as_str()) {
+        (Method::Get, "/session/s1/alert/text") => Some(Response::new(404, r#"{"value": {"error": "no such alert", "message": "", "stacktrace": ""}}"#)),
+        (Method::Post, "/session/s1/element") => Some(Response::new(500, r#"{"value": {"error": "unexpected alert open", "message": "", "stacktrace": "", "data": {"text": "Unexpected"}}}"#)),
+        (Method::Get, "/session/s1/title") => Some(Response::new(500, r#"{"value": {"error": "javascript error", "message": "", "stacktrace": "", "data": {"text": "Unrelated"}}}"#)),
+        _ => None
+    });
+
+    let session = SessionBuilder::new(Browser::Firefox).transport(webdriver).build().unwrap();
+    assert!(session.tabs[0].alert().unwrap().is_none());
+
+    // the text of an unexpected dialog is reported in the error
+    let error = session.tabs[0].find(Selector::Css, "body").err().unwrap();
+    assert_eq!(error, ErrorKind::UnexpectedAlertOpen);
+    assert_eq!(error.alert_text(), Some("Unexpected"));
+
+    let error = session.tabs[0].get_title().err().unwrap();
+    assert_eq!(error, ErrorKind::JavascriptError);
+    assert_eq!(error.alert_text(), None);
+}
+
+#[test]
+fn frame_switching() {
+    use lw_webdriver::frames::FrameId;
+    use std::sync::Arc;
+
+    assert!(FrameId::Top.to_json().is_null());
+    assert_eq!(FrameId::Index(2).to_json(), 2);
+
+    let webdriver = Arc::new(scripted(&["w1"], |call| match (call.method, call.path.as_str()) {
+        (Method::Post, "/session/s1/element") => Some(Response::new(200, r#"{"value": {"element-6066-11e4-a52e-4f735466cecf": "e1"}}"#)),
+        _ => None
+    }));
+    let mut session = SessionBuilder::new(Browser::Firefox).transport(Arc::clone(&webdriver)).build().unwrap();
+    let iframe = session.tabs[0].find(Selector::Css, "iframe").unwrap().unwrap();
+    assert_eq!(FrameId::Element((&iframe).into()).to_json(), iframe.as_json_object());
+
+    // the guards switch back to the parent frame when they are dropped
+    {
+        let mut frame = session.tabs[0].frame(FrameId::Element(iframe.into())).unwrap();
+        let _inner = frame.frame(FrameId::Index(0)).unwrap();
+    }
+    let requests: Vec<(String, json::JsonValue)> = webdriver.requests().iter()
+        .filter(|r| r.url.starts_with("http://localhost:4444/session/s1/frame"))
+        .map(|r| (r.url.trim_start_matches("http://localhost:4444").to_string(), json::parse(r.body.as_deref().unwrap_or("{}")).unwrap()))
+        .collect();
+    assert_eq!(requests.len(), 4);
+    assert_eq!(requests[0].0, "/session/s1/frame");
+    assert_eq!(requests[0].1["id"]["element-6066-11e4-a52e-4f735466cecf"], "e1");
+    assert_eq!(requests[1].0, "/session/s1/frame");
+    assert_eq!(requests[1].1["id"], 0);
+    assert_eq!(requests[2].0, "/session/s1/frame/parent");
+    assert_eq!(requests[3].0, "/session/s1/frame/parent");
+}
+
+#[test]
+fn frames() {
+    use lw_webdriver::frames::FrameId;
+    use lw_webdriver::error::ErrorKind;
+    use std::{thread::sleep, time::Duration};
+
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    for i in 0..2 {
+        let mut session = match i {
+            0 => {
+                info!("testing with Firefox");
+                Session::new(Browser::Firefox, true).unwrap()
+            },
+            _ => {
+                info!("testing with Chrome");
+                Session::new(Browser::Chrome, true).unwrap()
+            }
+        };
+
+        session.tabs[0].execute_script("document.body.innerHTML = '<iframe id=\"outer\" srcdoc=\"<p>outer</p><iframe srcdoc=&quot;<p>inner</p>&quot;></iframe>\"></iframe>';", vec![]).unwrap();
+        sleep(Duration::from_millis(500));
+
+        let outer = session.tabs[0].find(Selector::Css, "#outer").unwrap().unwrap();
+        {
+            let mut frame = session.tabs[0].frame(FrameId::Element(outer.into())).unwrap();
+            assert_eq!(frame.find(Selector::Css, "p").unwrap().unwrap().get_text().unwrap(), "outer");
+            {
+                let inner = frame.frame(FrameId::Index(0)).unwrap();
+                assert_eq!(inner.find(Selector::Css, "p").unwrap().unwrap().get_text().unwrap(), "inner");
+            }
+            assert_eq!(frame.find(Selector::Css, "p").unwrap().unwrap().get_text().unwrap(), "outer");
+        }
+        assert!(session.tabs[0].find(Selector::Css, "p").unwrap().is_none());
+
+        session.tabs[0].switch_to_frame(FrameId::Index(0)).unwrap();
+        session.tabs[0].switch_to_frame(FrameId::Index(0)).unwrap();
+        session.tabs[0].switch_to_parent_frame().unwrap();
+        assert_eq!(session.tabs[0].find(Selector::Css, "p").unwrap().unwrap().get_text().unwrap(), "outer");
+        session.tabs[0].switch_to_frame(FrameId::Top).unwrap();
+        assert!(session.tabs[0].find(Selector::Css, "p").unwrap().is_none());
+
+        assert_eq!(session.tabs[0].frame(FrameId::Index(5)).err().unwrap(), ErrorKind::NoSuchFrame);
+    }
+}
+
+#[test]
+fn window_rect() {
+    use lw_webdriver::window::WindowRect;
+
+    let rect = WindowRect::from_json(&object!{ "x" => -8, "y" => 0, "width" => 1024.0, "height" => 768 }).unwrap();
+    assert_eq!(rect, WindowRect::new(-8, 0, 1024, 768));
+    assert_eq!(rect.to_json()["x"], -8);
+    assert_eq!(rect.to_json()["width"], 1024);
+    assert_eq!(WindowRect::from_json(&rect.to_json()), Some(rect));
+    assert_eq!(WindowRect::from_json(&object!{ "x" => 0 }), None);
+}
+
+#[test]
+fn window() {
+    use lw_webdriver::window::WindowRect;
+
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    for i in 0..2 {
+        let session = match i {
+            0 => {
+                info!("testing with Firefox");
+                Session::new(Browser::Firefox, true).unwrap()
+            },
+            _ => {
+                info!("testing with Chrome");
+                Session::new(Browser::Chrome, true).unwrap()
+            }
+        };
+
+        let rect = session.tabs[0].set_window_rect(&WindowRect::new(0, 0, 800, 600)).unwrap();
+        assert_eq!((rect.width, rect.height), (800, 600));
+        assert_eq!(session.tabs[0].get_window_rect().unwrap(), rect);
+
+        session.tabs[0].set_viewport_size(640, 480).unwrap();
+        let viewport = session.tabs[0].execute_script("return [window.innerWidth, window.innerHeight];", vec![]).unwrap().into_array().unwrap();
+        assert_eq!(viewport[0].as_i64(), Some(640));
+        assert_eq!(viewport[1].as_i64(), Some(480));
+
+        session.tabs[0].set_window_size(1024, 768).unwrap();
+        session.tabs[0].maximize().unwrap();
+        session.tabs[0].fullscreen().unwrap();
+    }
+}
+
+#[test]
+fn wait_conditions() {
+    use lw_webdriver::wait::Wait;
+    use lw_webdriver::error::{WebdriverError, ErrorKind};
+    use std::error::Error;
+    use std::time::{Duration, Instant};
+
+    // a condition met at the third check
+    let wait = Wait::new(Duration::from_secs(1)).interval(Duration::from_millis(10));
+    let mut checks = 0;
+    let value = wait.until(|| {
+        checks += 1;
+        Ok(if checks == 3 { Some(checks) } else { None })
+    }).unwrap();
+    assert_eq!(value, 3);
+
+    // ignored errors are retried, the last one is the source of the timeout
+    let start = Instant::now();
+    let error = Wait::new(Duration::from_millis(100)).interval(Duration::from_millis(10)).ignore(ErrorKind::NoSuchElement)
+        .until_true(|| Err(ErrorKind::NoSuchElement.into())).err().unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(error, ErrorKind::Timeout);
+    assert!(error.message().unwrap().contains("condition"));
+    assert!(error.source().is_some());
+    let error = wait.until_true(|| Err(ErrorKind::StaleElementReference.into())).err().unwrap();
+    assert_eq!(error, ErrorKind::Timeout);
+
+    // other errors are returned immediately
+    let mut checks = 0;
+    let error = wait.until_true(|| {
+        checks += 1;
+        Err(WebdriverError::from(ErrorKind::JavascriptError))
+    }).err().unwrap();
+    assert_eq!(error, ErrorKind::JavascriptError);
+    assert_eq!(checks, 1);
+}
+
+#[test]
+fn wait() {
+    use lw_webdriver::wait::Wait;
+    use lw_webdriver::error::ErrorKind;
+    use std::time::Duration;
+
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    for i in 0..2 {
+        let session = match i {
+            0 => {
+                info!("testing with Firefox");
+                Session::new(Browser::Firefox, true).unwrap()
+            },
+            _ => {
+                info!("testing with Chrome");
+                Session::new(Browser::Chrome, true).unwrap()
+            }
+        };
+
+        session.tabs[0].execute_script("document.title = 'Loading'; document.body.innerHTML = '<div id=\"spinner\"></div><button id=\"submit\" disabled style=\"display: none\">Send</button>'; \
+            setTimeout(() => submit.style.display = 'block', 200); \
+            setTimeout(() => submit.disabled = false, 400); \
+            setTimeout(() => { spinner.remove(); submit.textContent = 'Sent'; document.title = 'Done'; history.pushState({}, '', '/orders/42'); }, 600);", vec![]).unwrap();
+
+        let wait = Wait::new(Duration::from_secs(5));
+        wait.for_element(&session.tabs[0], Selector::Css, "#submit").unwrap();
+        wait.for_visible(&session.tabs[0], Selector::Css, "#submit").unwrap();
+        let button = wait.for_clickable(&session.tabs[0], Selector::Css, "#submit").unwrap();
+        assert!(button.is_enabled().unwrap());
+        wait.for_text(&session.tabs[0], Selector::Css, "#submit", "Sent").unwrap();
+        wait.for_element_gone(&session.tabs[0], Selector::Css, "#spinner").unwrap();
+        wait.for_title(&session.tabs[0], "Done").unwrap();
+        assert!(wait.for_url(&session.tabs[0], "*/orders/*").unwrap().ends_with("/orders/42"));
+
+        let error = Wait::new(Duration::from_millis(200)).for_element(&session.tabs[0], Selector::Css, "#missing").err().unwrap();
+        assert_eq!(error, ErrorKind::Timeout);
+    }
+}
+
+#[test]
+fn transport() {
+    use lw_webdriver::error::ErrorKind;
+    use std::sync::Arc;
+
+    let webdriver = scripted(&["w1"], |call| match (call.method, call.path.as_str()) {
+        (Method::Get, "/session/s1/title") => Some(Response::new(200, r#"{"value": "Scripted"}"#)),
+        (Method::Post, "/session/s1/element") => Some(Response::new(404, r#"{"value": {"error": "no such element", "message": "nothing", "stacktrace": ""}}"#)),
+        (Method::Get, "/session/s1/source") => Some(Response::new(500, "<html>not json</html>")),
+        _ => None
+    });
+    let webdriver = Arc::new(webdriver.error(Method::Get, "/session/s1/url", "connection reset"));
+    let session = SessionBuilder::new(Browser::Firefox)
+        .webdriver_url("http://driver:9515/")
+        .transport(Arc::clone(&webdriver))
+        .build()
+        .unwrap();
+
+    let creation = webdriver.requests()[0].clone();
+    assert_eq!(creation.method, Method::Post);
+    assert_eq!(creation.url, "http://driver:9515/session");
+    assert_eq!(json::parse(creation.body.as_ref().unwrap()).unwrap()["capabilities"]["alwaysMatch"]["browserName"], "firefox");
+
+    assert_eq!(session.tabs[0].get_title().unwrap(), "Scripted");
+    assert!(session.tabs[0].find(Selector::Css, "#missing").unwrap().is_none());
+
+    let error = session.tabs[0].get_page_source().err().unwrap();
+    assert_eq!(error, ErrorKind::InvalidResponse);
+    assert_eq!(error.status(), Some(500));
+
+    let error = session.tabs[0].get_url().err().unwrap();
+    assert_eq!(error, ErrorKind::FailedRequest);
+    assert_eq!(error.command(), Some("GET /session/s1/url"));
+    assert_eq!(error.source().unwrap().to_string(), "connection reset");
+
+    session.quit().unwrap();
+    let last = webdriver.requests().last().unwrap().clone();
+    assert_eq!((last.method, last.url.as_str()), (Method::Delete, "http://driver:9515/session/s1"));
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn mock_server() {
+    use lw_webdriver::mock::*;
+    use lw_webdriver::{cookies::Cookie, error::ErrorKind, frames::FrameId, wait::Wait, window::WindowRect, keys::Keys};
+    use std::time::Duration;
+
+    catch_unwind(|| {
+        env_logger::init();
+    });
+
+    let server = MockServer::start().unwrap();
+    server.add_page("https://shop.test/", MockPage::new("Shop")
+        .element(MockElement::new("nav")
+            .child(MockElement::new("a").attribute("href", "/cart").text("Cart"))
+            .child(MockElement::new("a").class("hidden").displayed(false).text("Admin")))
+        .element(MockElement::new("form").id("search")
+            .child(MockElement::new("input").attribute("name", "q").attribute("value", "sho"))
+            .child(MockElement::new("button").class("btn primary").attribute("type", "submit").text("Search")))
+        .element(MockElement::new("input").id("terms").attribute("type", "checkbox"))
+        .element(MockElement::new("iframe").id("payment")
+            .child(MockElement::new("input").attribute("name", "card"))));
+    server.add_page("https://shop.test/cart", MockPage::new("Cart")
+        .element(MockElement::new("p").text("Empty")));
+    server.on_script(|script, args| {
+        if script == "return arguments[0] * 2;" {
+            (args[0].as_i64().unwrap() * 2).into()
+        } else {
+            json::JsonValue::Null
+        }
+    });
+
+    let mut session = SessionBuilder::new(Browser::Chrome).webdriver_url(server.get_url()).build().unwrap();
+    let tab = &mut session.tabs[0];
+    tab.navigate("https://shop.test/").unwrap();
+    assert_eq!(tab.get_title().unwrap(), "Shop");
+    assert!(tab.get_page_source().unwrap().starts_with("<html><head><title>Shop</title></head><body><nav>"));
+    assert!(tab.get_page_source().unwrap().contains("<button class=\"btn primary\" type=\"submit\">Search</button>"));
+
+    // selectors
+    assert_eq!(tab.find_all(Selector::Css, "nav > a").unwrap().len(), 2);
+    assert_eq!(tab.find_all(Selector::Css, "form input[name=q]").unwrap().len(), 1);
+    assert_eq!(tab.find(Selector::Css, "button.primary.btn").unwrap().unwrap().get_text().unwrap(), "Search");
+    assert_eq!(tab.find(Selector::XPath, "//*[@id=\"terms\"]").unwrap().unwrap().get_tag_name().unwrap(), "input");
+    assert_eq!(tab.find_all(Selector::TagName, "input").unwrap().len(), 2);
+    assert!(tab.find(Selector::Css, "body > form").unwrap().is_some());
+    assert!(tab.find(Selector::Css, "html > form").unwrap().is_none());
+    assert!(tab.find(Selector::Css, "input[name=card]").unwrap().is_none());
+    assert_eq!(tab.find(Selector::Css, "a:hover").err().unwrap(), ErrorKind::InvalidSelector);
+    let form = tab.find(Selector::Css, "#search").unwrap().unwrap();
+    assert_eq!(form.find_all(Selector::Css, "*").unwrap().len(), 2);
+
+    // elements
+    let hidden = tab.find(Selector::Css, ".hidden").unwrap().unwrap();
+    assert!(!hidden.is_displayed().unwrap());
+    assert_eq!(hidden.get_text().unwrap(), "");
+    assert_eq!(tab.find(Selector::Css, "nav").unwrap().unwrap().get_text().unwrap(), "Cart");
+    let input = form.find(Selector::Css, "input").unwrap().unwrap();
+    input.type_text(&format!("x{}es", Keys::Backspace)).unwrap();
+    assert_eq!(input.get_property("value").unwrap(), "shoes");
+    assert_eq!(input.get_attribute("value").unwrap(), "sho");
+    let terms = tab.find(Selector::Css, "#terms").unwrap().unwrap();
+    terms.click().unwrap();
+    assert_eq!(terms.get_property("checked").unwrap(), "true");
+    assert_eq!(tab.execute_script("return arguments[0] * 2;", vec![21.into()]).unwrap().as_i64(), Some(42));
+
+    // frames
+    {
+        let frame = tab.frame(FrameId::Index(0)).unwrap();
+        assert!(frame.find(Selector::Css, "input[name=card]").unwrap().is_some());
+        assert!(frame.find(Selector::Css, "#terms").unwrap().is_none());
+    }
+    assert!(tab.find(Selector::Css, "#terms").unwrap().is_some());
+
+    // navigation and stale elements
+    let cart = tab.find(Selector::LinkText, "Cart").unwrap().unwrap();
+    cart.click().unwrap();
+    assert_eq!(tab.get_url().unwrap(), "https://shop.test/cart");
+    assert_eq!(terms.get_text().err().unwrap(), ErrorKind::StaleElementReference);
+    Wait::new(Duration::from_secs(1)).for_title(tab, "Cart").unwrap();
+    tab.back().unwrap();
+    assert_eq!(tab.get_title().unwrap(), "Shop");
+    tab.forward().unwrap();
+    assert_eq!(tab.get_title().unwrap(), "Cart");
+
+    // cookies
+    tab.set_cookie(&Cookie::new("session", "42")).unwrap();
+    assert_eq!(tab.get_cookie("session").unwrap().unwrap().domain.as_deref(), Some("shop.test"));
+    assert_eq!(tab.set_cookie(&Cookie::new("other", "1").domain("example.com")).err().unwrap(), ErrorKind::InvalidCookieDomain);
+    tab.navigate("https://example.com/").unwrap();
+    assert!(tab.get_cookies().unwrap().is_empty());
+
+    // window, capture and dialogs
+    tab.set_viewport_size(800, 600).unwrap();
+    assert_eq!(tab.get_window_rect().unwrap(), WindowRect::new(0, 0, 800, 600));
+    assert!(tab.screenshot().unwrap().as_bytes().starts_with(b"\x89PNG"));
+    assert!(tab.print_pdf(&Default::default()).unwrap().starts_with(b"%PDF"));
+    server.open_alert("Leave?");
+    assert_eq!(tab.alert().unwrap().unwrap().text().unwrap(), "Leave?");
+    let error = tab.get_title().err().unwrap();
+    assert_eq!(error.alert_text(), Some("Leave?"));
+    assert!(tab.alert().unwrap().is_none());
+
+    // tabs
+    session.open_tab().unwrap();
+    session.tabs[1].navigate("https://shop.test/cart").unwrap();
+    assert_eq!(session.tabs[0].get_url().unwrap(), "https://example.com/");
+    assert_eq!(session.tabs[1].get_title().unwrap(), "Cart");
+
+    session.quit().unwrap();
+    let requests = server.get_requests();
+    assert!(requests.iter().any(|r| r.starts_with("DELETE /session/")));
+}
+
+#[test]
+fn cassette() {
+    use lw_webdriver::cassette::*;
+    use lw_webdriver::error::ErrorKind;
+    use std::sync::Arc;
+
+    /// A webdriver with a single tab showing a page
+    fn page() -> impl Transport {
+        scripted(&["w1"], |call| match (call.method, call.path.as_str()) {
+            (Method::Get, "/session/s1/title") => Some(Response::new(200, r#"{"value": "Recorded"}"#)),
+            (Method::Get, "/session/s1/source") => Some(Response::new(502, "Bad Gateway")),
+            _ => None
+        }).error(Method::Get, "/session/s1/url", "connection reset")
+    }
+
+    let path = std::env::temp_dir().join(format!("lw-webdriver-cassette-{}.json", std::process::id()));
+
+    // record
+    let session = SessionBuilder::new(Browser::Firefox).transport(RecordingTransport::new(page(), &path)).build().unwrap();
+    session.tabs[0].navigate("https://example.com/").unwrap();
+    assert_eq!(session.tabs[0].get_title().unwrap(), "Recorded");
+    assert_eq!(session.tabs[0].get_page_source().err().unwrap(), ErrorKind::InvalidResponse);
+    assert_eq!(session.tabs[0].get_url().err().unwrap(), ErrorKind::FailedRequest);
+    session.quit().unwrap();
+
+    // a cassette which can not be saved does not change the responses
+    let unwritable = std::env::temp_dir().join("lw-webdriver-missing-directory").join("cassette.json");
+    let session = SessionBuilder::new(Browser::Firefox).transport(RecordingTransport::new(page(), &unwritable)).build().unwrap();
+    assert_eq!(session.tabs[0].get_title().unwrap(), "Recorded");
+    session.quit().unwrap();
+    assert!(!unwritable.exists());
+
+    let cassette = Cassette::load(&path).unwrap();
+    assert_eq!(cassette.interactions[0].method, Method::Post);
+    assert_eq!(cassette.interactions[0].path, "/session");
+    assert_eq!(Cassette::from_json(&cassette.to_json()).unwrap(), cassette);
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains("\"url\": \"https://example.com/\""));
+    assert!(text.contains("\"bodyText\": \"Bad Gateway\""));
+    assert!(text.contains("\"error\": \"connection reset\""));
+
+    // replay the same scenario
+    let replay = Arc::new(ReplayTransport::load(&path).unwrap());
+    let session = SessionBuilder::new(Browser::Firefox).transport(Arc::clone(&replay)).build().unwrap();
+    session.tabs[0].navigate("https://example.com/").unwrap();
+    assert_eq!(session.tabs[0].get_title().unwrap(), "Recorded");
+    let error = session.tabs[0].get_page_source().err().unwrap();
+    assert_eq!(error.status(), Some(502));
+    assert_eq!(error.kind, ErrorKind::InvalidResponse);
+    assert_eq!(session.tabs[0].get_url().err().unwrap().source().unwrap().to_string(), "connection reset");
+    session.quit().unwrap();
+    assert_eq!(replay.remaining(), 0);
+
+    // another scenario does not match the cassette
+    let replay = ReplayTransport::new(cassette);
+    let session = SessionBuilder::new(Browser::Firefox).transport(replay).build().unwrap();
+    let error = session.tabs[0].get_title().err().unwrap();
+    assert_eq!(error, ErrorKind::FailedRequest);
+    assert_eq!(error.source().unwrap().to_string(), "expected POST /session/s1/url but got GET /session/s1/title");
+    std::mem::drop(session);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "async")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn async_api() {
+    use lw_webdriver::frames::FrameId;
+    use lw_webdriver::error::ErrorKind;
+
+    // the titles are the handles of the windows, and the element e1 is an iframe
+    let webdriver = scripted(&["w1", "w2"], |call| match (call.method, call.path.as_str()) {
+        (Method::Get, "/session/s1/title") => Some(Response::new(200, &json::stringify(object!{"value" => call.window.as_str()}))),
+        (Method::Post, "/session/s1/element") => Some(Response::new(200, r#"{"value": {"element-6066-11e4-a52e-4f735466cecf": "e1"}}"#)),
+        (Method::Get, "/session/s1/element/e1/text") => Some(Response::new(200, r#"{"value": "Hello"}"#)),
+        (Method::Post, "/session/s1/execute/sync") => Some(Response::new(200, r#"{"value": {"count": 1, "first": {"element-6066-11e4-a52e-4f735466cecf": "e1"}}}"#)),
+        (Method::Post, "/session/s1/elements") => Some(Response::new(404, r#"{"value": {"error": "invalid selector", "message": "", "stacktrace": ""}}"#)),
+        (Method::Post, "/session/s1/frame") => match call.body["id"]["element-6066-11e4-a52e-4f735466cecf"].as_str() {
+            Some("e1") => None,
+            _ => Some(Response::new(404, r#"{"value": {"error": "no such frame", "message": "", "stacktrace": ""}}"#))
+        },
+        _ => None
+    });
+
+    let session = SessionBuilder::new(Browser::Firefox)
+        .async_transport(webdriver)
+        .build_async()
+        .await
+        .unwrap();
+    assert_eq!(session.tabs.len(), 2);
+
+    // tabs used concurrently from several tasks get their own responses
+    let mut tasks = Vec::new();
+    for i in 0..20 {
+        let tab = session.tabs[i % 2].clone();
+        tasks.push(tokio::spawn(async move {
+            let title = tab.get_title().await.unwrap();
+            assert_eq!(&title, tab.get_id());
+        }));
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let element = session.tabs[1].find(Selector::Css, "p").await.unwrap().unwrap();
+    assert_eq!(element.get_text().await.unwrap(), "Hello");
+    let value = session.tabs[1].execute_script("return {count: 1, first: document.querySelector('p')};", vec![]).await.unwrap();
+    assert_eq!(value.get("count").unwrap().as_i64(), Some(1));
+    assert_eq!(value.get("first").unwrap().as_element(), Some(&element));
+    assert_eq!(value.to_json()["first"], element.as_json_object());
+    assert_eq!(session.tabs[1].find_all(Selector::Css, "p:").await.err().unwrap(), ErrorKind::InvalidSelector);
+
+    // async elements can be used as frames
+    session.tabs[1].switch_to_frame(FrameId::Element(element.into())).await.unwrap();
+    assert_eq!(session.tabs[1].switch_to_frame(FrameId::Index(3)).await.err().unwrap(), ErrorKind::NoSuchFrame);
+    session.quit().await.unwrap();
+}
+
+#[cfg(all(feature = "async", feature = "mock"))]
+#[tokio::test]
+async fn async_mock_server() {
+    use lw_webdriver::mock::*;
+
+    let server = MockServer::start().unwrap();
+    server.add_page("https://shop.test/", MockPage::new("Shop")
+        .element(MockElement::new("a").attribute("href", "/cart").text("Cart")));
+    server.add_page("https://shop.test/cart", MockPage::new("Cart"));
+
+    // requests are sent with reqwest
+    let mut session = lw_webdriver::asynchronous::AsyncSession::new_with_url(server.get_url(), Browser::Firefox, true).await.unwrap();
+    session.open_tab().await.unwrap();
+    session.tabs[0].navigate("https://shop.test/").await.unwrap();
+    session.tabs[1].navigate("https://shop.test/cart").await.unwrap();
+    assert_eq!(session.tabs[0].get_title().await.unwrap(), "Shop");
+    assert_eq!(session.tabs[1].get_title().await.unwrap(), "Cart");
+
+    let link = session.tabs[0].find(Selector::Css, "a").await.unwrap().unwrap();
+    assert_eq!(link.get_text().await.unwrap(), "Cart");
+    link.click().await.unwrap();
+    assert_eq!(session.tabs[0].get_url().await.unwrap(), "https://shop.test/cart");
+    session.quit().await.unwrap();
+}
+
+#[test]
+fn thread_safety() {
+    use lw_webdriver::{tab::Tab, elements::Element, frames::FrameId};
+    use std::thread;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Session>();
+    assert_send_sync::<Tab>();
+    assert_send_sync::<Element>();
+
+    // the titles are the handles of the windows followed by the frames entered
+    let frames = Mutex::new(Vec::new());
+    let webdriver = scripted(&["w1", "w2"], move |call| {
+        let mut frames = frames.lock().unwrap();
+        match (call.method, call.path.as_str()) {
+            (Method::Post, "/session/s1/window") => frames.clear(),
+            (Method::Post, "/session/s1/frame") => frames.push(call.body["id"].to_string()),
+            (Method::Post, "/session/s1/frame/parent") => { frames.pop(); },
+            (Method::Get, "/session/s1/title") => {
+                let title = std::iter::once(call.window.clone()).chain(frames.iter().cloned()).collect::<Vec<String>>().join("/");
+                return Some(Response::new(200, &json::stringify(object!{"value" => title})));
+            },
+            (Method::Post, "/session/s1/element") => return Some(Response::new(200, &json::stringify(object!{
+                "value" => object!{ "element-6066-11e4-a52e-4f735466cecf" => format!("element-of-{}", call.window) }
+            }))),
+            _ => ()
+        }
+        None
+    });
+
+    let session = SessionBuilder::new(Browser::Firefox).transport(webdriver).build().unwrap();
+
+    // the session can be moved to another thread
+    let session = thread::spawn(move || {
+        assert_eq!(session.tabs.len(), 2);
+        session
+    }).join().unwrap();
+
+    // a shared session can be used concurrently by several threads, and tabs get their own responses
+    thread::scope(|scope| {
+        for tab in session.tabs.iter().chain(session.tabs.iter()) {
+            scope.spawn(move || {
+                for _ in 0..20 {
+                    assert_eq!(&tab.get_title().unwrap(), tab.get_id());
+                    tab.navigate("https://example.com/").unwrap();
+                    let element = tab.find(Selector::Css, "p").unwrap().unwrap();
+                    assert_eq!(element.get_id(), &format!("element-of-{}", tab.get_id()));
+                }
+            });
+        }
+    });
+
+    // a frame guard stays in its frame while another thread uses another tab
+    let mut session = session;
+    let (first, others) = session.tabs.split_at_mut(1);
+    let second = &others[0];
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            let mut frame = first[0].frame(FrameId::Index(0)).unwrap();
+            for _ in 0..50 {
+                assert_eq!(frame.get_title().unwrap(), "w1/0");
+            }
+            let inner = frame.frame(FrameId::Index(1)).unwrap();
+            for _ in 0..50 {
+                assert_eq!(inner.get_title().unwrap(), "w1/0/1");
+            }
+        });
+        scope.spawn(move || {
+            for _ in 0..100 {
+                assert_eq!(second.get_title().unwrap(), "w2");
+            }
+        });
+    });
+    assert_eq!(session.tabs[0].get_title().unwrap(), "w1");
+
+    session.quit().unwrap();
+}